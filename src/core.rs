@@ -1,4 +1,23 @@
+pub mod asset_pack;
+pub mod auto_threshold;
 pub mod bitmap;
+pub mod cache;
+pub mod c_export;
+pub mod check;
+pub mod color;
+pub mod dolphin;
+pub mod edges;
+pub mod font;
+pub mod formats;
+pub mod heatshrink;
 pub mod img2bm;
+pub mod info;
 pub mod meta;
 pub mod params;
+pub mod preview;
+pub mod scene;
+pub mod sharpen;
+pub mod temporal;
+pub mod terminal;
+pub mod text;
+pub mod watch;
@@ -0,0 +1,104 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::ext::path_ext::{EXT_GIF, EXT_PICTURE};
+
+
+pub const ICONS: &str = "Icons";
+pub const ANIMS: &str = "Anims";
+/// The size, frame rate and frame count of an animated icon
+pub const ICON_META_FILE: &str = "meta";
+const TARGETS_FILE: &str = "targets.txt";
+
+pub enum TargetKind {
+    /// The width and height the icon is drawn with by the firmware
+    Icon(u8, u8),
+    Anim,
+}
+
+pub struct Target {
+    /// The path in the pack without an extension, such as Icons/Passport/passport_happy_46x49
+    pub name: String,
+    pub source: PathBuf,
+    pub kind: TargetKind,
+}
+
+impl Target {
+
+    /// The directory in the pack the target is written into, and the file name without an extension
+    pub fn split(&self) -> (String, String) {
+        let (dir, name) = self.name.rsplit_once('/').unwrap_or(("", &self.name));
+        return (format!("{dir}/"), name.to_string());
+    }
+}
+
+/// The targets of the targets.txt lines in the source folder,
+/// or of the pictures and animations in its Icons and Anims folders
+pub fn targets(dir: &Path) -> Result<Vec<Target>, String> {
+    let targets_path = dir.join(TARGETS_FILE);
+    if targets_path.exists() {
+        let text = fs::read_to_string(&targets_path).map_err(|it| it.to_string())?;
+        return text.lines()
+            .map(|it| it.trim())
+            .filter(|it| !it.is_empty() && !it.starts_with('#'))
+            .map(|line| {
+                let (name, source) = line.split_once('=').ok_or(format!("'{line}' isn't a 'target = source' line"))?;
+                target(name.trim(), dir.join(source.trim()))
+            })
+            .collect();
+    }
+    let mut sources = Vec::new();
+    for folder in [ICONS, ANIMS] {
+        find_sources(&dir.join(folder), &mut sources);
+    }
+    sources.sort();
+    return sources.into_iter()
+        .map(|source| {
+            let relative = source.strip_prefix(dir).unwrap().with_extension("");
+            let name = relative.components()
+                .map(|it| it.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<String>>()
+                .join("/");
+            target(&name, source)
+        })
+        .collect();
+}
+
+fn target(name: &str, source: PathBuf) -> Result<Target, String> {
+    let name = name.trim_matches('/').to_string();
+    let kind = match name.split_once('/') {
+        Some((ANIMS, rest)) if !rest.contains('/') => TargetKind::Anim,
+        Some((ICONS, rest)) if rest.contains('/') => {
+            let (width, height) = size_of(&name).ok_or(format!("'{name}' doesn't end with the icon size such as _46x49"))?;
+            TargetKind::Icon(width, height)
+        },
+        _ => return Err(format!("'{name}' is neither {ICONS}/<Category>/<Name> nor {ANIMS}/<Name>")),
+    };
+    if !source.is_file() {
+        return Err(format!("'{}' of '{name}' doesn't exist", source.display()));
+    }
+    return Ok(Target { name, source, kind });
+}
+
+/// The size of the name ending such as _46x49
+pub fn size_of(name: &str) -> Option<(u8, u8)> {
+    let (_, size) = name.rsplit_once('_')?;
+    let (width, height) = size.split_once('x')?;
+    let width = width.parse::<u8>().ok().filter(|&it| it > 0)?;
+    let height = height.parse::<u8>().ok().filter(|&it| it > 0)?;
+    return Some((width, height));
+}
+
+fn find_sources(dir: &Path, sources: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+    for entry in entries.filter_map(|it| it.ok()) {
+        let path = entry.path();
+        let ext = path.extension()
+            .map(|it| it.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if path.is_dir() {
+            find_sources(&path, sources);
+        } else if ext == EXT_GIF || EXT_PICTURE.contains(&ext.as_str()) {
+            sources.push(path);
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use image::{GrayAlphaImage, LumaA};
+
+
+const LEVELS: usize = 256;
+
+pub type Histogram = [u32; LEVELS];
+
+pub fn histogram(image: &GrayAlphaImage) -> Histogram {
+    let mut histogram = [0u32; LEVELS];
+    add_to_histogram(&mut histogram, image);
+    return histogram;
+}
+
+/// Only the pixels with a non-zero alpha are counted
+pub fn add_to_histogram(histogram: &mut Histogram, image: &GrayAlphaImage) {
+    for pixel in image.pixels() {
+        let [luminance, alpha] = pixel.0;
+        if alpha != 0 {
+            histogram[luminance as usize] += 1;
+        }
+    }
+}
+
+/// Otsu's method, returns the level in 0.0..=1.0 that maximizes the between-class variance
+pub fn otsu_level(histogram: &Histogram) -> f32 {
+    let total = histogram.iter().map(|&it| it as u64).sum::<u64>();
+    if total == 0 {
+        return 0.5;
+    }
+    let sum_all = histogram.iter()
+        .enumerate()
+        .map(|(level, &count)| level as f64 * count as f64)
+        .sum::<f64>();
+    let mut sum_dark = 0f64;
+    let mut weight_dark = 0u64;
+    let mut best_variance = -1f64;
+    let mut best_level = 0usize;
+    for (level, &count) in histogram.iter().enumerate() {
+        weight_dark += count as u64;
+        if weight_dark == 0 {
+            continue;
+        }
+        let weight_light = total - weight_dark;
+        if weight_light == 0 {
+            break;
+        }
+        sum_dark += level as f64 * count as f64;
+        let mean_dark = sum_dark / weight_dark as f64;
+        let mean_light = (sum_all - sum_dark) / weight_light as f64;
+        let variance = weight_dark as f64 * weight_light as f64 * (mean_dark - mean_light).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best_level = level;
+        }
+    }
+    // pixels with the level equal to the found one belong to the dark class
+    return (best_level as f32 + 1.0) / LEVELS as f32;
+}
+
+/// Shifts every pixel by the difference between 50% and the mean of its neighbourhood,
+/// so a fixed threshold around 50% becomes a local one
+pub fn local_contrast(image: &GrayAlphaImage, radius: u32, gaussian: bool) -> GrayAlphaImage {
+    let means = match gaussian {
+        true => gaussian_means(image, radius),
+        false => box_means(image, radius),
+    };
+    let mut result = GrayAlphaImage::new(image.width(), image.height());
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let [luminance, alpha] = pixel.0;
+        let mean = means[(y * image.width() + x) as usize];
+        let value = luminance as f32 - mean + 127.5;
+        result.put_pixel(x, y, LumaA([value.round().clamp(0.0, 255.0) as u8, alpha]));
+    }
+    return result;
+}
+
+fn box_means(image: &GrayAlphaImage, radius: u32) -> Vec<f32> {
+    let width = image.width() as usize;
+    let height = image.height() as usize;
+    // summed-area table with an extra zero row and column
+    let mut integral = vec![0u64; (width + 1) * (height + 1)];
+    for y in 0..height {
+        let mut row = 0u64;
+        for x in 0..width {
+            row += image.get_pixel(x as u32, y as u32).0[0] as u64;
+            integral[(y + 1) * (width + 1) + x + 1] = integral[y * (width + 1) + x + 1] + row;
+        }
+    }
+    let radius = radius as usize;
+    let mut means = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let top = y.saturating_sub(radius);
+        let bottom = (y + radius + 1).min(height);
+        for x in 0..width {
+            let left = x.saturating_sub(radius);
+            let right = (x + radius + 1).min(width);
+            let sum = integral[bottom * (width + 1) + right] + integral[top * (width + 1) + left]
+                - integral[top * (width + 1) + right] - integral[bottom * (width + 1) + left];
+            let count = (bottom - top) * (right - left);
+            means.push(sum as f32 / count as f32);
+        }
+    }
+    return means;
+}
+
+pub fn gaussian_means(image: &GrayAlphaImage, radius: u32) -> Vec<f32> {
+    let width = image.width() as usize;
+    let height = image.height() as usize;
+    let kernel = gaussian_kernel(radius);
+    let radius = radius as i32;
+    let source = image.pixels().map(|it| it.0[0] as f32).collect::<Vec<f32>>();
+    let mut horizontal = vec![0f32; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            horizontal[y * width + x] = convolve(&kernel, radius, |offset| {
+                let x = (x as i32 + offset).clamp(0, width as i32 - 1) as usize;
+                source[y * width + x]
+            });
+        }
+    }
+    let mut means = vec![0f32; width * height];
+    for y in 0..height {
+        for x in 0..width {
+            means[y * width + x] = convolve(&kernel, radius, |offset| {
+                let y = (y as i32 + offset).clamp(0, height as i32 - 1) as usize;
+                horizontal[y * width + x]
+            });
+        }
+    }
+    return means;
+}
+
+fn gaussian_kernel(radius: u32) -> Vec<f32> {
+    let sigma = (radius as f32 / 2.0).max(0.5);
+    let radius = radius as i32;
+    let kernel = (-radius..=radius)
+        .map(|it| (-(it * it) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect::<Vec<f32>>();
+    let sum = kernel.iter().sum::<f32>();
+    return kernel.iter().map(|it| it / sum).collect();
+}
+
+fn convolve<F>(kernel: &[f32], radius: i32, value: F) -> f32 where F: Fn(i32) -> f32 {
+    kernel.iter()
+        .enumerate()
+        .map(|(index, weight)| weight * value(index as i32 - radius))
+        .sum()
+}
@@ -1,7 +1,7 @@
 use std::ops::{Shl, Shr};
 
 
-#[derive(Hash)]
+#[derive(Hash, Clone)]
 pub struct Bitmap {
     pub width: u8,
     pub height: u8,
@@ -10,25 +10,87 @@ pub struct Bitmap {
     pub dy: i32,
 }
 
+/// How the source pixels are combined with the destination ones
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BlitMode {
+    Copy, Or, And, Xor,
+}
+
 impl Bitmap {
 
+    /// The first byte is the uncompressed .bm header, the rest are allocated for all the pixels,
+    /// every row starts with a new byte
     pub fn new(width: u8, height: u8, dx: i32, dy: i32) -> Bitmap {
-        Bitmap { width, height, bytes: vec![0x00], dx, dy }
+        let size = Bitmap::stride(width) * height as usize;
+        Bitmap { width, height, bytes: vec![0x00; size + 1], dx, dy }
+    }
+
+    /// Bytes per row
+    pub fn stride(width: u8) -> usize {
+        (width as usize).div_ceil(8)
+    }
+
+    /// The pixel bytes without the header
+    pub fn data(&self) -> &[u8] {
+        &self.bytes[1..]
+    }
+
+    pub fn with_size(width: u8, height: u8) -> Bitmap {
+        Bitmap::new(width, height, 0, 0)
+    }
+
+    /// The bitmap of the pixel bytes without the header, the missing bytes are left empty
+    pub fn from_data(width: u8, height: u8, data: &[u8]) -> Bitmap {
+        let mut bitmap = Bitmap::with_size(width, height);
+        let length = data.len().min(bitmap.bytes.len() - 1);
+        bitmap.bytes[1..=length].copy_from_slice(&data[..length]);
+        return bitmap;
     }
 
     pub fn set(&mut self, x: u32, y: u32) {
         let (byte, bit) = self.get_indexes(x, y);
-        while self.bytes.len() <= byte {
-            self.bytes.push(0x00)
-        }
         let bit: u8 = 1u8.shl(bit);
         self.bytes[byte] |= bit;
     }
 
+    pub fn set_to(&mut self, x: u32, y: u32, value: bool) {
+        if value {
+            self.set(x, y);
+            return;
+        }
+        let (byte, bit) = self.get_indexes(x, y);
+        self.bytes[byte] &= !1u8.shl(bit);
+    }
+
+    /// Like set_to(), but the pixels out of bounds are skipped
+    pub fn put(&mut self, x: i32, y: i32, value: bool) {
+        if self.contains(x, y) {
+            self.set_to(x as u32, y as u32, value);
+        }
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= 0 && y >= 0 && x < self.width as i32 && y < self.height as i32
+    }
+
+    pub fn clear(&mut self) {
+        for index in 1..self.bytes.len() {
+            self.bytes[index] = 0x00;
+        }
+    }
+
     pub fn invert(&mut self) {
         for index in 1..self.bytes.len() {
             self.bytes[index] = !self.bytes[index];
         }
+        // the unused bits of the last byte in a row stay zero
+        let used = self.width as usize % 8;
+        if used != 0 {
+            let stride = Bitmap::stride(self.width);
+            for row in 1..=self.height as usize {
+                self.bytes[row * stride] &= 1u8.shl(used) - 1;
+            }
+        }
     }
 
     pub fn get(&self, x: u32, y: u32) -> bool {
@@ -46,8 +108,131 @@ impl Bitmap {
         dst_y as i32 + self.dy
     }
 
+    /// Bresenham's line including both ends
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, value: bool) {
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let step_x = if x0 < x1 { 1 } else { -1 };
+        let step_y = if y0 < y1 { 1 } else { -1 };
+        let mut error = dx + dy;
+        let (mut x, mut y) = (x0, y0);
+        loop {
+            self.put(x, y, value);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let double = error * 2;
+            if double >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if double <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    pub fn draw_rect(&mut self, x: i32, y: i32, width: u32, height: u32, value: bool) {
+        if width == 0 || height == 0 {
+            return;
+        }
+        let right = x + width as i32 - 1;
+        let bottom = y + height as i32 - 1;
+        self.draw_line(x, y, right, y, value);
+        self.draw_line(x, bottom, right, bottom, value);
+        self.draw_line(x, y, x, bottom, value);
+        self.draw_line(right, y, right, bottom, value);
+    }
+
+    pub fn fill_rect(&mut self, x: i32, y: i32, width: u32, height: u32, value: bool) {
+        for y in y..(y + height as i32) {
+            for x in x..(x + width as i32) {
+                self.put(x, y, value);
+            }
+        }
+    }
+
+    /// Midpoint circle
+    pub fn draw_circle(&mut self, center_x: i32, center_y: i32, radius: u32, value: bool) {
+        self.for_each_octant(radius, |bitmap, x, y| {
+            for (dx, dy) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)] {
+                bitmap.put(center_x + dx, center_y + dy, value);
+            }
+        });
+    }
+
+    pub fn fill_circle(&mut self, center_x: i32, center_y: i32, radius: u32, value: bool) {
+        self.for_each_octant(radius, |bitmap, x, y| {
+            bitmap.draw_line(center_x - x, center_y + y, center_x + x, center_y + y, value);
+            bitmap.draw_line(center_x - x, center_y - y, center_x + x, center_y - y, value);
+            bitmap.draw_line(center_x - y, center_y + x, center_x + y, center_y + x, value);
+            bitmap.draw_line(center_x - y, center_y - x, center_x + y, center_y - x, value);
+        });
+    }
+
+    /// Sets the 4-connected area of the pixels equal to the start one
+    pub fn flood_fill(&mut self, x: i32, y: i32, value: bool) {
+        if !self.contains(x, y) || self.get(x as u32, y as u32) == value {
+            return;
+        }
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            if !self.contains(x, y) || self.get(x as u32, y as u32) == value {
+                continue;
+            }
+            self.set_to(x as u32, y as u32, value);
+            stack.extend([(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]);
+        }
+    }
+
+    /// Draws the other bitmap with its top left corner at the position
+    pub fn blit(&mut self, other: &Bitmap, x: i32, y: i32, mode: BlitMode) {
+        for src_y in 0..other.height as u32 {
+            for src_x in 0..other.width as u32 {
+                let dst_x = x + src_x as i32;
+                let dst_y = y + src_y as i32;
+                if !self.contains(dst_x, dst_y) {
+                    continue;
+                }
+                let src = other.get(src_x, src_y);
+                let dst = self.get(dst_x as u32, dst_y as u32);
+                let value = match mode {
+                    BlitMode::Copy => src,
+                    BlitMode::Or => dst || src,
+                    BlitMode::And => dst && src,
+                    BlitMode::Xor => dst != src,
+                };
+                self.set_to(dst_x as u32, dst_y as u32, value);
+            }
+        }
+    }
+
+    /// The area of the bitmap, the pixels out of bounds are unset
+    pub fn crop(&self, x: i32, y: i32, width: u8, height: u8) -> Bitmap {
+        let mut result = Bitmap::with_size(width, height);
+        result.blit(self, -x, -y, BlitMode::Copy);
+        return result;
+    }
+
+    fn for_each_octant<F>(&mut self, radius: u32, mut action: F) where F: FnMut(&mut Bitmap, i32, i32) {
+        let mut x = radius as i32;
+        let mut y = 0;
+        let mut error = 1 - x;
+        while x >= y {
+            action(self, x, y);
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+    }
+
     fn get_indexes(&self, x: u32, y: u32) -> (usize, usize) {
-        let offset = (self.width as u32 * y + x) as usize;
-        (offset / 8 + 1, offset % 8)
+        let offset = Bitmap::stride(self.width) * y as usize + x as usize / 8;
+        (offset + 1, x as usize % 8)
     }
 }
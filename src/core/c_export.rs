@@ -0,0 +1,87 @@
+use crate::core::bitmap::Bitmap;
+use crate::core::heatshrink::pack;
+use crate::core::params::c_array::CArray;
+use crate::core::params::layout::Layout;
+use crate::core::formats::to_layout;
+
+
+pub struct CSource {
+    pub header: String,
+    pub source: String,
+}
+
+/// The frames as const uint8_t arrays in the Flipper image format,
+/// or headerless in the layout if it isn't the Flipper one,
+/// the order lists the frame indexes the animation is played with
+pub fn c_source(
+    name: &str,
+    frames: &[Bitmap],
+    order: &[usize],
+    frame_rate: u32,
+    kind: CArray,
+    layout: Layout,
+    icon: bool,
+) -> CSource {
+    let name = identifier(name);
+    let upper = name.to_uppercase();
+    let first = frames.first().unwrap();
+    let animated = order.len() > 1;
+    let icon_name = format!("{}_{name}", if animated { "A" } else { "I" });
+    let mut header = String::from("#pragma once\n\n#include <stdint.h>\n");
+    if icon {
+        header += "#include <gui/icon_i.h>\n";
+    }
+    header += &format!("\n#define {upper}_WIDTH {}\n#define {upper}_HEIGHT {}\n", first.width, first.height);
+    let mut source = format!("#include \"{name}.h\"\n\n");
+    if !animated {
+        header += &format!("\nextern const uint8_t {name}[];\nextern const uint8_t* const {name}_frames[];\n");
+        source += &array(&name, first, kind, layout);
+        source += &format!("\nconst uint8_t* const {name}_frames[] = {{{name}}};\n");
+    } else {
+        header += &format!("#define {upper}_FRAME_COUNT {}\n#define {upper}_FRAME_RATE {frame_rate}\n\n", order.len());
+        for (index, frame) in frames.iter().enumerate() {
+            header += &format!("extern const uint8_t {name}_{index}[];\n");
+            source += &array(&format!("{name}_{index}"), frame, kind, layout);
+        }
+        header += &format!("extern const uint8_t* const {name}_frames[];\n");
+        let order = order.iter()
+            .map(|it| format!("{name}_{it}"))
+            .collect::<Vec<String>>()
+            .join(", ");
+        source += &format!("\nconst uint8_t* const {name}_frames[] = {{{order}}};\n");
+    }
+    if icon {
+        let frame_count = order.len().max(1);
+        let frame_rate = if animated { frame_rate } else { 0 };
+        header += &format!("\nextern const Icon {icon_name};\n");
+        source += &format!(
+            "\nconst Icon {icon_name} = {{.width={},.height={},.frame_count={frame_count},.frame_rate={frame_rate},.frames={name}_frames}};\n",
+            first.width, first.height,
+        );
+    }
+    return CSource { header, source };
+}
+
+fn array(name: &str, bitmap: &Bitmap, kind: CArray, layout: Layout) -> String {
+    let bytes = match kind {
+        _ if layout != Layout::HorizontalLsb => to_layout(bitmap, layout),
+        CArray::Raw => bitmap.bytes.clone(),
+        CArray::Compressed => pack(bitmap.data()),
+    };
+    let lines = bytes.chunks(16)
+        .map(|chunk| chunk.iter().map(|it| format!("0x{it:02x},")).collect::<Vec<String>>().join(""))
+        .collect::<Vec<String>>()
+        .join("\n    ");
+    return format!("const uint8_t {name}[] = {{\n    {lines}\n}};\n");
+}
+
+/// The name with the chars that aren't allowed in a C identifier replaced with '_'
+pub fn identifier(name: &str) -> String {
+    let mut result = name.chars()
+        .map(|it| if it.is_ascii_alphanumeric() { it } else { '_' })
+        .collect::<String>();
+    if result.chars().next().is_none_or(|it| it.is_ascii_digit()) {
+        result.insert(0, '_');
+    }
+    return result;
+}
@@ -0,0 +1,90 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+
+
+/// The file in the asset pack, a line per target: the name, the key and the output path in the pack
+pub const CACHE_FILE: &str = ".img2fbm_cache";
+
+/// What the asset pack targets were built from and into
+pub struct Cache {
+    dir: PathBuf,
+    entries: HashMap<String, (u64, String)>,
+}
+
+impl Cache {
+
+    /// The cache of the previous build in the pack directory, empty if there is none
+    pub fn load(dir: &Path) -> Cache {
+        let entries = fs::read_to_string(dir.join(CACHE_FILE)).unwrap_or_default()
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\t');
+                let name = fields.next()?.to_string();
+                let key = u64::from_str_radix(fields.next()?, 16).ok()?;
+                let output = fields.next()?.to_string();
+                Some((name, (key, output)))
+            })
+            .collect();
+        return Cache { dir: dir.to_path_buf(), entries };
+    }
+
+    /// Whether the target was built with the key into the output, and the output is still there
+    pub fn is_fresh(&self, name: &str, key: u64, output: &str) -> bool {
+        match self.entries.get(name) {
+            Some((it, path)) => *it == key && path == output && self.dir.join(output).exists(),
+            None => false,
+        }
+    }
+
+    /// The previous output of the target is removed if it's built into another one now
+    pub fn insert(&mut self, name: &str, key: u64, output: &str) {
+        let previous = self.entries.insert(name.to_string(), (key, output.to_string()));
+        if let Some((_, previous)) = previous.filter(|(_, it)| it != output) {
+            remove(&self.dir.join(previous));
+        }
+    }
+
+    /// Removes the outputs of the targets that aren't built anymore, the names of them
+    pub fn clean(&mut self, names: &HashSet<String>) -> Vec<String> {
+        let mut gone = self.entries.keys()
+            .filter(|it| !names.contains(*it))
+            .cloned()
+            .collect::<Vec<String>>();
+        gone.sort();
+        for name in &gone {
+            let (_, output) = self.entries.remove(name).unwrap();
+            remove(&self.dir.join(output));
+        }
+        return gone;
+    }
+
+    pub fn save(&self) {
+        let mut lines = self.entries.iter()
+            .map(|(name, (key, output))| format!("{name}\t{key:016x}\t{output}\n"))
+            .collect::<Vec<String>>();
+        lines.sort();
+        fs::write(self.dir.join(CACHE_FILE), lines.concat()).unwrap();
+    }
+}
+
+/// The hash of the files and the values, a missing file counts as empty.
+/// The hasher isn't stable between the Rust versions, a new one just rebuilds everything once
+pub fn key(files: &[&Path], values: &[&str]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for file in files {
+        fs::read(file).unwrap_or_default().hash(&mut hasher);
+    }
+    values.hash(&mut hasher);
+    return hasher.finish();
+}
+
+fn remove(path: &Path) {
+    if path.is_dir() {
+        fs::remove_dir_all(path).ok();
+    } else {
+        fs::remove_file(path).ok();
+    }
+}
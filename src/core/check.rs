@@ -0,0 +1,223 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+use crate::core::dolphin::{parse_manifest, parse_meta, ManifestEntry, Meta, MANIFEST_FILE, META_FILE};
+use crate::core::heatshrink::unpack;
+
+
+const MAX_WIDTH: u32 = 128;
+const MAX_HEIGHT: u32 = 64;
+const MAX_BUTTHURT: u32 = 14;
+const ALIGN_H: [&str; 3] = ["Left", "Center", "Right"];
+const ALIGN_V: [&str; 3] = ["Top", "Center", "Bottom"];
+
+#[derive(PartialEq, Eq)]
+pub enum Severity {
+    /// The dolphin service fails or misbehaves with it
+    Error,
+    Warning,
+}
+
+pub struct Issue {
+    pub severity: Severity,
+    /// The file in the dolphin directory
+    pub path: String,
+    pub message: String,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{severity}: {}: {}", self.path, self.message)
+    }
+}
+
+struct Issues(Vec<Issue>);
+
+impl Issues {
+    fn error(&mut self, path: &str, message: String) {
+        self.0.push(Issue { severity: Severity::Error, path: path.to_string(), message });
+    }
+
+    fn warning(&mut self, path: &str, message: String) {
+        self.0.push(Issue { severity: Severity::Warning, path: path.to_string(), message });
+    }
+}
+
+/// The issues of the manifest.txt in the dolphin directory and of the animations it refers to
+pub fn check(dir: &Path) -> Vec<Issue> {
+    let mut issues = Issues(Vec::new());
+    let entries = match fs::read_to_string(dir.join(MANIFEST_FILE)) {
+        Err(e) => {
+            issues.error(MANIFEST_FILE, e.to_string());
+            return issues.0;
+        },
+        Ok(text) => match parse_manifest(&text) {
+            Err(msg) => {
+                issues.error(MANIFEST_FILE, msg);
+                return issues.0;
+            },
+            Ok(entries) => entries,
+        },
+    };
+    if entries.is_empty() {
+        issues.error(MANIFEST_FILE, String::from("no animations"));
+    }
+    let mut names = HashSet::new();
+    for entry in &entries {
+        if !names.insert(entry.name.as_str()) {
+            issues.error(MANIFEST_FILE, format!("{} is listed more than once", entry.name));
+            continue;
+        }
+        check_entry(entry, &mut issues);
+        check_anim(dir, &entry.name, &mut issues);
+    }
+    if let Ok(dirs) = fs::read_dir(dir) {
+        let mut unlisted = dirs.filter_map(|it| it.ok())
+            .filter(|it| it.path().is_dir())
+            .filter_map(|it| it.file_name().to_str().map(String::from))
+            .filter(|it| !names.contains(it.as_str()))
+            .collect::<Vec<String>>();
+        unlisted.sort();
+        for name in unlisted {
+            issues.warning(&name, format!("isn't listed in {MANIFEST_FILE}"));
+        }
+    }
+    return issues.0;
+}
+
+fn check_entry(entry: &ManifestEntry, issues: &mut Issues) {
+    let name = &entry.name;
+    if entry.min_butthurt > entry.max_butthurt || entry.max_butthurt > MAX_BUTTHURT {
+        issues.error(MANIFEST_FILE, format!("{name} has the butthurt {}..{} out of 0..{MAX_BUTTHURT}", entry.min_butthurt, entry.max_butthurt));
+    }
+    if entry.min_level > entry.max_level || entry.min_level == 0 {
+        issues.error(MANIFEST_FILE, format!("{name} has the level {}..{}", entry.min_level, entry.max_level));
+    }
+    if entry.weight == 0 {
+        issues.warning(MANIFEST_FILE, format!("{name} has the weight 0 and is never shown"));
+    }
+}
+
+fn check_anim(dir: &Path, name: &str, issues: &mut Issues) {
+    let anim_dir = dir.join(name);
+    let meta_path = format!("{name}/{META_FILE}");
+    if !anim_dir.is_dir() {
+        issues.error(name, String::from("the folder doesn't exist"));
+        return;
+    }
+    let meta = match fs::read_to_string(anim_dir.join(META_FILE)).map_err(|e| e.to_string()).and_then(|it| parse_meta(&it)) {
+        Ok(meta) => meta,
+        Err(msg) => return issues.error(&meta_path, msg),
+    };
+    check_meta(&meta, &meta_path, issues);
+    check_frames(&meta, &anim_dir, name, issues);
+    check_bubbles(&meta, &meta_path, issues);
+}
+
+fn check_meta(meta: &Meta, path: &str, issues: &mut Issues) {
+    if meta.width == 0 || meta.height == 0 || meta.width > MAX_WIDTH || meta.height > MAX_HEIGHT {
+        issues.error(path, format!("the size {}x{} is out of {MAX_WIDTH}x{MAX_HEIGHT}", meta.width, meta.height));
+    }
+    let frames = meta.passive_frames + meta.active_frames;
+    if frames as usize != meta.frames_order.len() {
+        issues.error(path, format!("Passive frames {} and Active frames {} make {frames}, but Frames order has {}",
+            meta.passive_frames, meta.active_frames, meta.frames_order.len()));
+    }
+    if meta.passive_frames == 0 {
+        issues.warning(path, String::from("no passive frames to play while idle"));
+    }
+    if meta.frame_rate == 0 {
+        issues.error(path, String::from("the frame rate is 0"));
+    }
+    if meta.active_frames > 0 && meta.active_cycles == 0 {
+        issues.warning(path, String::from("the active frames are never played with 0 active cycles"));
+    }
+}
+
+fn check_frames(meta: &Meta, anim_dir: &Path, name: &str, issues: &mut Issues) {
+    let size = (meta.width as usize).div_ceil(8) * meta.height as usize;
+    let unique = meta.unique_frames();
+    // the frames are loaded from 0 up to the highest one
+    let count = unique.last().map_or(0, |it| it + 1);
+    for index in 0..count {
+        let file = format!("frame_{index}.bm");
+        let path = format!("{name}/{file}");
+        let Ok(bytes) = fs::read(anim_dir.join(&file)) else {
+            issues.error(&path, String::from("doesn't exist"));
+            continue;
+        };
+        if !unique.contains(&index) {
+            issues.warning(&path, String::from("Frames order doesn't refer to it"));
+        }
+        // the compressed ones are checked by their unpacked pixels too
+        match unpack(&bytes) {
+            Some(pixels) if pixels.len() == size => (),
+            Some(pixels) => issues.error(&path, format!("{} pixel bytes instead of {size} for {}x{}", pixels.len(), meta.width, meta.height)),
+            None => issues.error(&path, String::from("isn't a raw or compressed bitmap")),
+        }
+    }
+}
+
+fn check_bubbles(meta: &Meta, path: &str, issues: &mut Issues) {
+    let frames = meta.passive_frames + meta.active_frames;
+    for bubble in &meta.bubbles {
+        let slot = bubble.slot;
+        if slot >= meta.bubble_slots {
+            issues.error(path, format!("the bubble slot {slot} is out of Bubble slots {}", meta.bubble_slots));
+        }
+        if bubble.start_frame > bubble.end_frame || bubble.end_frame >= frames {
+            issues.error(path, format!("the bubble of slot {slot} is shown at frames {}..{} out of 0..{}",
+                bubble.start_frame, bubble.end_frame, frames.saturating_sub(1)));
+        }
+        if !ALIGN_H.contains(&bubble.align_h.as_str()) || !ALIGN_V.contains(&bubble.align_v.as_str()) {
+            issues.error(path, format!("the bubble of slot {slot} has the alignment {} {}", bubble.align_h, bubble.align_v));
+        }
+        if bubble.x >= meta.width || bubble.y >= meta.height {
+            issues.warning(path, format!("the bubble of slot {slot} at {}:{} is out of the animation", bubble.x, bubble.y));
+        }
+        if bubble.text.is_empty() {
+            issues.warning(path, format!("the bubble of slot {slot} has no text"));
+        }
+    }
+    for slot in 0..meta.bubble_slots {
+        if !meta.bubbles.iter().any(|it| it.slot == slot) {
+            issues.error(path, format!("the bubble slot {slot} has no bubbles"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::core::check::{check, Severity};
+    use crate::core::dolphin::{MANIFEST_FILE, META_FILE};
+    use crate::core::heatshrink::pack;
+    use crate::core::meta::{get_manifest, get_meta, FrameData};
+    use crate::core::params::recipe::{ManifestValues, Recipe};
+
+    /// A compressed frame is checked by its unpacked size, not by its declared length only
+    #[test]
+    fn compressed_frame_of_wrong_size() {
+        let dir = std::env::temp_dir().join(format!("img2fbm_check_{}", std::process::id()));
+        let anim_dir = dir.join("anim");
+        fs::create_dir_all(&anim_dir).unwrap();
+        fs::write(dir.join(MANIFEST_FILE), get_manifest(true, String::from("anim"), &ManifestValues::default())).unwrap();
+        let frames = [FrameData { index: 0, duration: 100.0 }, FrameData { index: 1, duration: 100.0 }].into();
+        fs::write(anim_dir.join(META_FILE), get_meta(128, 64, &frames, &Recipe::default())).unwrap();
+        // 128x64 is 1024 bytes, the second frame is of 128x32
+        fs::write(anim_dir.join("frame_0.bm"), pack(&[0; 1024])).unwrap();
+        fs::write(anim_dir.join("frame_1.bm"), pack(&[0; 512])).unwrap();
+        let issues = check(&dir);
+        fs::remove_dir_all(&dir).unwrap();
+        let errors = issues.iter()
+            .filter(|it| it.severity == Severity::Error)
+            .map(|it| it.path.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(errors, ["anim/frame_1.bm"]);
+    }
+}
@@ -0,0 +1,89 @@
+use image::{Rgba, RgbaImage};
+
+const BYTE_LIMIT: u16 = 256;
+const TRANSPARENT: Rgba<u8> = Rgba([255, 255, 255, 0]);
+const VISIBLE: Rgba<u8> = Rgba([0, 0, 0, 255]);
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    pub fn parse(value: u32) -> Color {
+        let limit = BYTE_LIMIT as u32;
+        Color {
+            r: (value / limit.pow(2) % limit) as u8,
+            g: (value / limit % limit) as u8,
+            b: (value % limit) as u8,
+        }
+    }
+
+    pub fn of(pixel: &Rgba<u8>) -> Color {
+        Color { r: pixel[0], g: pixel[1], b: pixel[2] }
+    }
+
+    pub fn matches(&self, pixel: &Rgba<u8>, tolerance: u8) -> bool {
+        let r = pixel[0];
+        let g = pixel[1];
+        let b = pixel[2];
+        // the difference between channels keeps the hue close, not only the brightness
+        let hue_tolerance = (tolerance as u16 * 3 / 5) as u8;
+        match () {
+            _ if abs_dif(self.r, r) > tolerance => false,
+            _ if abs_dif(self.g, g) > tolerance => false,
+            _ if abs_dif(self.b, b) > tolerance => false,
+            _ if abs_dif(abs_dif(self.r, self.g), abs_dif(r, g)) > hue_tolerance => false,
+            _ if abs_dif(abs_dif(self.g, self.b), abs_dif(g, b)) > hue_tolerance => false,
+            _ => true,
+        }
+    }
+}
+
+/// The corner color that most of the other corners match
+pub fn corners_color(image: &RgbaImage, tolerance: u8) -> Color {
+    let right = image.width() - 1;
+    let bottom = image.height() - 1;
+    let corners = [(0, 0), (right, 0), (0, bottom), (right, bottom)]
+        .map(|(x, y)| *image.get_pixel(x, y));
+    let mut best = Color::of(&corners[0]);
+    let mut best_count = 0;
+    for corner in corners.iter() {
+        let color = Color::of(corner);
+        let count = corners.iter().filter(|it| color.matches(it, tolerance)).count();
+        if count > best_count {
+            best = color;
+            best_count = count;
+        }
+    }
+    return best;
+}
+
+pub fn remove_background(image: &mut RgbaImage, color: Color, tolerance: u8, to_visible: bool) {
+    let target = if to_visible { VISIBLE } else { TRANSPARENT };
+    for pixel in image.pixels_mut() {
+        if pixel[3] != 0 && color.matches(pixel, tolerance) {
+            *pixel = target;
+        }
+    }
+}
+
+pub fn color_to_u32(color: &str) -> Result<u32, String> {
+    let color = color.trim_start_matches('#');
+    if color.len() != 6 && color.len() != 8 {
+        return Err(format!("Color must contains 6 or 8 0..f-chars ({})", color));
+    }
+    let mut color_int = u32::from_str_radix(color, 16).map_err(|err| err.to_string())?;
+    if color.len() == 6 {
+        color_int += 0xff000000;
+    }
+    return Ok(color_int);
+}
+
+fn abs_dif(first: u8, second: u8) -> u8 {
+    let mut dif = first as i16 - second as i16;
+    dif *= dif.signum();
+    dif as u8
+}
@@ -0,0 +1,150 @@
+use std::str::FromStr;
+use serde::Deserialize;
+
+
+pub const MANIFEST_FILE: &str = "manifest.txt";
+pub const META_FILE: &str = "meta.txt";
+const MANIFEST_FILETYPE: &str = "Flipper Animation Manifest";
+const META_FILETYPE: &str = "Flipper Animation";
+
+pub struct ManifestEntry {
+    pub name: String,
+    pub min_butthurt: u32,
+    pub max_butthurt: u32,
+    pub min_level: u32,
+    pub max_level: u32,
+    pub weight: u32,
+}
+
+pub struct Meta {
+    pub width: u32,
+    pub height: u32,
+    pub passive_frames: u32,
+    pub active_frames: u32,
+    pub frames_order: Vec<u32>,
+    pub active_cycles: u32,
+    pub frame_rate: u32,
+    pub duration: u32,
+    pub active_cooldown: u32,
+    pub bubble_slots: u32,
+    pub bubbles: Vec<Bubble>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Bubble {
+    pub slot: u32,
+    pub x: u32,
+    pub y: u32,
+    pub text: String,
+    pub align_h: String,
+    pub align_v: String,
+    pub start_frame: u32,
+    pub end_frame: u32,
+}
+
+impl Meta {
+
+    /// The frame indexes the order refers to, each once and sorted
+    pub fn unique_frames(&self) -> Vec<u32> {
+        let mut frames = self.frames_order.clone();
+        frames.sort();
+        frames.dedup();
+        return frames;
+    }
+}
+
+/// The "key: value" lines of a Flipper Format file in their order
+pub fn fields(text: &str) -> Vec<(&str, &str)> {
+    text.lines()
+        .filter(|it| !it.trim_start().starts_with('#'))
+        .filter_map(|it| it.split_once(':'))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect()
+}
+
+pub fn parse_manifest(text: &str) -> Result<Vec<ManifestEntry>, String> {
+    let fields = fields(text);
+    check_header(&fields, MANIFEST_FILETYPE)?;
+    return groups(&fields, "Name").iter()
+        .map(|group| manifest_entry(group).map_err(|it| format!("{}: {it}", group[0].1)))
+        .collect();
+}
+
+fn manifest_entry(group: &[(&str, &str)]) -> Result<ManifestEntry, String> {
+    return Ok(ManifestEntry {
+        name: value(group, "Name")?,
+        min_butthurt: value(group, "Min butthurt")?,
+        max_butthurt: value(group, "Max butthurt")?,
+        min_level: value(group, "Min level")?,
+        max_level: value(group, "Max level")?,
+        weight: value(group, "Weight")?,
+    });
+}
+
+pub fn parse_meta(text: &str) -> Result<Meta, String> {
+    let fields = fields(text);
+    check_header(&fields, META_FILETYPE)?;
+    let first_slot = fields.iter().position(|&(key, _)| key == "Slot").unwrap_or(fields.len());
+    let main = &fields[..first_slot];
+    let frames_order = value::<String>(main, "Frames order")?
+        .split_whitespace()
+        .map(|it| it.parse::<u32>().map_err(|_| format!("'{it}' of Frames order isn't a frame index")))
+        .collect::<Result<Vec<u32>, String>>()?;
+    let bubbles = groups(&fields[first_slot..], "Slot").iter()
+        .map(|group| bubble(group).map_err(|it| format!("the bubble of Slot {}: {it}", group[0].1)))
+        .collect::<Result<Vec<Bubble>, String>>()?;
+    return Ok(Meta {
+        width: value(main, "Width")?,
+        height: value(main, "Height")?,
+        passive_frames: value(main, "Passive frames")?,
+        active_frames: value(main, "Active frames")?,
+        frames_order,
+        active_cycles: value(main, "Active cycles")?,
+        frame_rate: value(main, "Frame rate")?,
+        duration: value(main, "Duration")?,
+        active_cooldown: value(main, "Active cooldown")?,
+        bubble_slots: value(main, "Bubble slots")?,
+        bubbles,
+    });
+}
+
+fn bubble(group: &[(&str, &str)]) -> Result<Bubble, String> {
+    return Ok(Bubble {
+        slot: value(group, "Slot")?,
+        x: value(group, "X")?,
+        y: value(group, "Y")?,
+        text: value(group, "Text")?,
+        align_h: value(group, "AlignH")?,
+        align_v: value(group, "AlignV")?,
+        start_frame: value(group, "StartFrame")?,
+        end_frame: value(group, "EndFrame")?,
+    });
+}
+
+fn check_header(fields: &[(&str, &str)], filetype: &str) -> Result<(), String> {
+    match fields.first() {
+        Some(&("Filetype", value)) if value == filetype => Ok(()),
+        _ => Err(format!("the file doesn't start with 'Filetype: {filetype}'")),
+    }
+}
+
+/// The fields split before every key, the fields ahead of the first key are dropped
+fn groups<'a>(fields: &'a [(&'a str, &'a str)], key: &str) -> Vec<&'a [(&'a str, &'a str)]> {
+    let starts = fields.iter()
+        .enumerate()
+        .filter(|&(_, &(it, _))| it == key)
+        .map(|(i, _)| i)
+        .collect::<Vec<usize>>();
+    return starts.iter()
+        .enumerate()
+        .map(|(i, &start)| &fields[start..*starts.get(i + 1).unwrap_or(&fields.len())])
+        .collect();
+}
+
+fn value<T: FromStr>(group: &[(&str, &str)], key: &str) -> Result<T, String> {
+    let (_, value) = group.iter()
+        .find(|&&(it, _)| it == key)
+        .ok_or(format!("no '{key}'"))?;
+    return value.parse::<T>().map_err(|_| format!("'{value}' isn't a valid {key}"));
+}
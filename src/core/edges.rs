@@ -0,0 +1,170 @@
+use image::{GrayAlphaImage, GrayImage, Luma};
+use crate::core::params::edges::Edges;
+
+
+const EDGE: Luma<u8> = Luma([255]);
+// the magnitude of the Sobel operator on a black and white step
+const MAX_MAGNITUDE: f32 = 4.0 * 255.0;
+
+/// The mask of the edge pixels of the stroke width
+pub fn find_edges(image: &GrayAlphaImage, edges: Edges, stroke: u8) -> GrayImage {
+    let mask = match edges {
+        Edges::Sobel(level) => sobel(image, level),
+        Edges::Canny(low, high) => canny(image, low, high),
+        Edges::Outline => outline(image),
+    };
+    return dilate(&mask, stroke);
+}
+
+fn sobel(image: &GrayAlphaImage, level: u8) -> GrayImage {
+    let luma = luminances(image);
+    let (gx, gy) = gradients(&luma, image.width(), image.height());
+    let level = level as f32 / 100.0 * MAX_MAGNITUDE;
+    return GrayImage::from_fn(image.width(), image.height(), |x, y| {
+        let index = (y * image.width() + x) as usize;
+        match gx[index].hypot(gy[index]) >= level && level > 0.0 {
+            true => EDGE,
+            false => Luma([0]),
+        }
+    });
+}
+
+fn canny(image: &GrayAlphaImage, low: u8, high: u8) -> GrayImage {
+    let width = image.width() as i32;
+    let height = image.height() as i32;
+    let luma = blur(&luminances(image), width, height);
+    let (gx, gy) = gradients(&luma, image.width(), image.height());
+    let magnitude = gx.iter().zip(gy.iter()).map(|(x, y)| x.hypot(*y)).collect::<Vec<f32>>();
+    let at = |x: i32, y: i32| -> f32 {
+        match x < 0 || y < 0 || x >= width || y >= height {
+            true => 0.0,
+            false => magnitude[(y * width + x) as usize],
+        }
+    };
+    // non-maximum suppression along the gradient direction
+    let mut thin = vec![0f32; magnitude.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let index = (y * width + x) as usize;
+            let value = magnitude[index];
+            let angle = gy[index].atan2(gx[index]).to_degrees().rem_euclid(180.0);
+            let (dx, dy) = match angle {
+                _ if !(22.5..157.5).contains(&angle) => (1, 0),
+                _ if angle < 67.5 => (1, 1),
+                _ if angle < 112.5 => (0, 1),
+                _ => (-1, 1),
+            };
+            if value >= at(x + dx, y + dy) && value >= at(x - dx, y - dy) {
+                thin[index] = value;
+            }
+        }
+    }
+    let low = low as f32 / 100.0 * MAX_MAGNITUDE;
+    let high = high as f32 / 100.0 * MAX_MAGNITUDE;
+    let mut mask = GrayImage::new(image.width(), image.height());
+    let mut stack = Vec::<(i32, i32)>::new();
+    for y in 0..height {
+        for x in 0..width {
+            let value = thin[(y * width + x) as usize];
+            if value > 0.0 && value >= high {
+                mask.put_pixel(x as u32, y as u32, EDGE);
+                stack.push((x, y));
+            }
+        }
+    }
+    // hysteresis, the weak edges connected to the strong ones are kept
+    while let Some((x, y)) = stack.pop() {
+        for (dx, dy) in NEIGHBOURS {
+            let (x, y) = (x + dx, y + dy);
+            if x < 0 || y < 0 || x >= width || y >= height {
+                continue;
+            }
+            let value = thin[(y * width + x) as usize];
+            if value > 0.0 && value >= low && mask.get_pixel(x as u32, y as u32) != &EDGE {
+                mask.put_pixel(x as u32, y as u32, EDGE);
+                stack.push((x, y));
+            }
+        }
+    }
+    return mask;
+}
+
+/// The pixels that aren't a background with a background neighbour,
+/// the picture bounds aren't a border
+fn outline(image: &GrayAlphaImage) -> GrayImage {
+    let width = image.width() as i32;
+    let height = image.height() as i32;
+    let is_background = |x: i32, y: i32| -> bool {
+        x >= 0 && y >= 0 && x < width && y < height && image.get_pixel(x as u32, y as u32).0[1] == 0
+    };
+    return GrayImage::from_fn(image.width(), image.height(), |x, y| {
+        let (x, y) = (x as i32, y as i32);
+        match !is_background(x, y) && NEIGHBOURS.iter().any(|(dx, dy)| is_background(x + dx, y + dy)) {
+            true => EDGE,
+            false => Luma([0]),
+        }
+    });
+}
+
+fn dilate(mask: &GrayImage, stroke: u8) -> GrayImage {
+    if stroke <= 1 {
+        return mask.clone();
+    }
+    let from = -((stroke as i32 - 1) / 2);
+    let to = stroke as i32 / 2;
+    let width = mask.width() as i32;
+    let height = mask.height() as i32;
+    let mut result = GrayImage::new(mask.width(), mask.height());
+    for (x, y, pixel) in mask.enumerate_pixels() {
+        if pixel != &EDGE {
+            continue;
+        }
+        for dy in from..=to {
+            for dx in from..=to {
+                let (x, y) = (x as i32 + dx, y as i32 + dy);
+                if x >= 0 && y >= 0 && x < width && y < height {
+                    result.put_pixel(x as u32, y as u32, EDGE);
+                }
+            }
+        }
+    }
+    return result;
+}
+
+const NEIGHBOURS: [(i32, i32); 8] = [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)];
+
+fn luminances(image: &GrayAlphaImage) -> Vec<f32> {
+    image.pixels().map(|it| it.0[0] as f32).collect()
+}
+
+fn gradients(luma: &[f32], width: u32, height: u32) -> (Vec<f32>, Vec<f32>) {
+    let width = width as i32;
+    let height = height as i32;
+    let at = |x: i32, y: i32| luma[(y.clamp(0, height - 1) * width + x.clamp(0, width - 1)) as usize];
+    let mut gx = Vec::with_capacity(luma.len());
+    let mut gy = Vec::with_capacity(luma.len());
+    for y in 0..height {
+        for x in 0..width {
+            gx.push(at(x + 1, y - 1) + 2.0 * at(x + 1, y) + at(x + 1, y + 1)
+                - at(x - 1, y - 1) - 2.0 * at(x - 1, y) - at(x - 1, y + 1));
+            gy.push(at(x - 1, y + 1) + 2.0 * at(x, y + 1) + at(x + 1, y + 1)
+                - at(x - 1, y - 1) - 2.0 * at(x, y - 1) - at(x + 1, y - 1));
+        }
+    }
+    return (gx, gy);
+}
+
+// 3x3 binomial blur before Canny
+fn blur(luma: &[f32], width: i32, height: i32) -> Vec<f32> {
+    let at = |x: i32, y: i32| luma[(y.clamp(0, height - 1) * width + x.clamp(0, width - 1)) as usize];
+    let mut result = Vec::with_capacity(luma.len());
+    for y in 0..height {
+        for x in 0..width {
+            let sum = 4.0 * at(x, y)
+                + 2.0 * (at(x - 1, y) + at(x + 1, y) + at(x, y - 1) + at(x, y + 1))
+                + at(x - 1, y - 1) + at(x + 1, y - 1) + at(x - 1, y + 1) + at(x + 1, y + 1);
+            result.push(sum / 16.0);
+        }
+    }
+    return result;
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::fs;
+use crate::core::params::font_source::FontSource;
+
+
+const PRIMARY: &str = include_str!("../../fonts/primary.bdf");
+const SECONDARY: &str = include_str!("../../fonts/secondary.bdf");
+const KEYBOARD: &str = include_str!("../../fonts/keyboard.bdf");
+const BIG_NUMBERS: &str = include_str!("../../fonts/big_numbers.bdf");
+
+pub struct Glyph {
+    /// The horizontal distance to the next glyph origin
+    pub advance: i32,
+    pub width: u32,
+    pub height: u32,
+    /// The offset of the bitmap left side from the origin
+    pub dx: i32,
+    /// The offset of the bitmap bottom side above the baseline
+    pub dy: i32,
+    /// Rows from the top, the highest bit of the first byte is the left pixel
+    pub rows: Vec<Vec<u8>>,
+}
+
+impl Glyph {
+
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        self.rows.get(y as usize)
+            .and_then(|row| row.get(x as usize / 8))
+            .map(|byte| (byte << (x % 8)) & 0x80 != 0)
+            .unwrap_or(false)
+    }
+}
+
+pub struct Font {
+    /// The pixels above the baseline
+    pub ascent: i32,
+    /// The pixels below the baseline
+    pub descent: i32,
+    pub glyphs: HashMap<char, Glyph>,
+}
+
+impl Font {
+
+    /// The firmware font or the BDF file
+    pub fn load(source: &FontSource) -> Result<Font, String> {
+        let content = match source {
+            FontSource::Primary => PRIMARY,
+            FontSource::Secondary => SECONDARY,
+            FontSource::Keyboard => KEYBOARD,
+            FontSource::BigNumbers => BIG_NUMBERS,
+            FontSource::File(path) => {
+                let path = path.display();
+                let content = fs::read_to_string(path.to_string()).map_err(|err| format!("{path}: {err}"))?;
+                return Font::parse_bdf(&content).map_err(|err| format!("{path}: {err}"));
+            },
+        };
+        return Font::parse_bdf(content).map_err(|err| format!("{source}: {err}"));
+    }
+
+    pub fn parse_bdf(content: &str) -> Result<Font, String> {
+        let mut ascent = None;
+        let mut descent = None;
+        let mut bounds_height = 0;
+        let mut glyphs = HashMap::new();
+        let mut lines = content.lines();
+        let number = |value: Option<&str>| -> Result<i32, String> {
+            value.and_then(|it| it.parse::<i32>().ok()).ok_or(String::from("invalid BDF number"))
+        };
+        while let Some(line) = lines.next() {
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("FONT_ASCENT") => ascent = Some(number(words.next())?),
+                Some("FONT_DESCENT") => descent = Some(number(words.next())?),
+                Some("FONTBOUNDINGBOX") => {
+                    bounds_height = number(words.nth(1))?;
+                },
+                Some("STARTCHAR") => {
+                    let mut encoding = -1;
+                    let mut advance = 0;
+                    let (mut width, mut height, mut dx, mut dy) = (0, 0, 0, 0);
+                    let mut rows = Vec::new();
+                    while let Some(line) = lines.next() {
+                        let mut words = line.split_whitespace();
+                        match words.next() {
+                            Some("ENCODING") => encoding = number(words.next())?,
+                            Some("DWIDTH") => advance = number(words.next())?,
+                            Some("BBX") => {
+                                width = number(words.next())?;
+                                height = number(words.next())?;
+                                dx = number(words.next())?;
+                                dy = number(words.next())?;
+                            },
+                            Some("BITMAP") => {
+                                for _ in 0..height {
+                                    let row = lines.next().ok_or(String::from("unexpected end of BDF"))?.trim();
+                                    let bytes = (0..row.len() / 2)
+                                        .map(|i| u8::from_str_radix(&row[i * 2..i * 2 + 2], 16))
+                                        .collect::<Result<Vec<u8>, _>>()
+                                        .map_err(|_| format!("invalid BDF bitmap row: {row}"))?;
+                                    rows.push(bytes);
+                                }
+                            },
+                            Some("ENDCHAR") => break,
+                            _ => (),
+                        }
+                    }
+                    if let Some(char) = char::from_u32(encoding as u32).filter(|_| encoding >= 0) {
+                        let glyph = Glyph { advance, width: width as u32, height: height as u32, dx, dy, rows };
+                        glyphs.insert(char, glyph);
+                    }
+                },
+                _ => (),
+            }
+        }
+        if glyphs.is_empty() {
+            return Err(String::from("no glyphs found"));
+        }
+        let descent = descent.unwrap_or(0);
+        let ascent = ascent.unwrap_or(bounds_height - descent);
+        return Ok(Font { ascent, descent, glyphs });
+    }
+
+    pub fn height(&self) -> i32 {
+        self.ascent + self.descent
+    }
+
+    pub fn width_of(&self, text: &str) -> i32 {
+        text.chars()
+            .filter_map(|it| self.glyphs.get(&it))
+            .map(|it| it.advance)
+            .sum()
+    }
+}
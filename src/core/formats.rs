@@ -0,0 +1,109 @@
+use crate::core::bitmap::Bitmap;
+use crate::core::c_export::identifier;
+use crate::core::heatshrink::pack;
+use crate::core::params::output_format::OutputFormat;
+use crate::core::params::layout::Layout;
+
+
+/// The file content of the bitmap in the format, the name is used by the formats that are C sources
+pub fn encode(bitmap: &Bitmap, format: OutputFormat, layout: Layout, name: &str) -> Vec<u8> {
+    match format {
+        OutputFormat::Bm => bitmap.bytes.clone(),
+        OutputFormat::Raw => to_layout(bitmap, layout),
+        OutputFormat::Xbm => to_xbm(bitmap, name).into_bytes(),
+        OutputFormat::Pbm => to_pbm(bitmap),
+        OutputFormat::PbmAscii => to_pbm_ascii(bitmap).into_bytes(),
+        OutputFormat::Png | OutputFormat::Icon => to_png(bitmap),
+        OutputFormat::Bmx => to_bmx(bitmap),
+    }
+}
+
+/// The same LSB-first padded rows as .bm has, but without the header byte
+pub fn to_xbm(bitmap: &Bitmap, name: &str) -> String {
+    let name = identifier(name);
+    let bytes = bitmap.data().chunks(12)
+        .map(|chunk| chunk.iter().map(|it| format!("0x{it:02x}")).collect::<Vec<String>>().join(", "))
+        .collect::<Vec<String>>()
+        .join(",\n   ");
+    return format!("#define {name}_width {}
+#define {name}_height {}
+static unsigned char {name}_bits[] = {{
+   {bytes} }};
+", bitmap.width, bitmap.height);
+}
+
+/// P4, the rows are padded to bytes with the leftmost pixel in the highest bit
+pub fn to_pbm(bitmap: &Bitmap) -> Vec<u8> {
+    let mut result = format!("P4\n{} {}\n", bitmap.width, bitmap.height).into_bytes();
+    result.extend(bitmap.data().iter().map(|it| it.reverse_bits()));
+    return result;
+}
+
+/// P1, 1 is a visible pixel
+pub fn to_pbm_ascii(bitmap: &Bitmap) -> String {
+    let mut result = format!("P1\n{} {}\n", bitmap.width, bitmap.height);
+    for y in 0..bitmap.height as u32 {
+        let row = (0..bitmap.width as u32)
+            .map(|x| if bitmap.get(x, y) { "1" } else { "0" })
+            .collect::<Vec<&str>>()
+            .join(" ");
+        result += &row;
+        result += "\n";
+    }
+    return result;
+}
+
+/// 1-bit grayscale PNG, the rows are padded to bytes with the leftmost pixel in the highest bit
+/// and a visible pixel is 0, the black one
+pub fn to_png(bitmap: &Bitmap) -> Vec<u8> {
+    let mut result = Vec::new();
+    let mut encoder = png::Encoder::new(&mut result, bitmap.width as u32, bitmap.height as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::One);
+    let data = bitmap.data().iter().map(|it| !it.reverse_bits()).collect::<Vec<u8>>();
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&data).unwrap();
+    writer.finish().unwrap();
+    return result;
+}
+
+/// The width and height as little-endian u32 and the pixel bytes with the Flipper image header
+pub fn to_bmx(bitmap: &Bitmap) -> Vec<u8> {
+    let mut result = Vec::new();
+    result.extend((bitmap.width as u32).to_le_bytes());
+    result.extend((bitmap.height as u32).to_le_bytes());
+    result.extend(pack(bitmap.data()));
+    return result;
+}
+
+/// The meta file of an asset pack animated icon, little-endian u32 values
+pub fn to_bmx_meta(width: u8, height: u8, frame_rate: u32, frame_count: usize) -> Vec<u8> {
+    return [width as u32, height as u32, frame_rate, frame_count as u32].iter()
+        .flat_map(|it| it.to_le_bytes())
+        .collect();
+}
+
+/// Headerless bytes, the pages are 8 rows high and go from the top,
+/// a page byte per column from the left
+pub fn to_layout(bitmap: &Bitmap, layout: Layout) -> Vec<u8> {
+    match layout {
+        Layout::HorizontalLsb => bitmap.data().to_vec(),
+        Layout::HorizontalMsb => bitmap.data().iter().map(|it| it.reverse_bits()).collect(),
+        Layout::PagesLsb | Layout::PagesMsb => {
+            let mut result = Vec::new();
+            for page in 0..(bitmap.height as u32).div_ceil(8) {
+                for x in 0..bitmap.width as u32 {
+                    let mut byte = 0u8;
+                    for bit in 0..8 {
+                        let y = page * 8 + bit;
+                        if y < bitmap.height as u32 && bitmap.get(x, y) {
+                            byte |= 1 << bit;
+                        }
+                    }
+                    result.push(if layout == Layout::PagesMsb { byte.reverse_bits() } else { byte });
+                }
+            }
+            result
+        },
+    }
+}
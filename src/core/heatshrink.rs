@@ -0,0 +1,150 @@
+// heatshrink LZSS as the Flipper firmware assets use it
+const WINDOW_BITS: u32 = 8;
+const LOOKAHEAD_BITS: u32 = 4;
+const WINDOW_SIZE: usize = 1 << WINDOW_BITS;
+const LOOKAHEAD_SIZE: usize = 1 << LOOKAHEAD_BITS;
+// a back-reference is 1 + 8 + 4 bits, a literal is 1 + 8 bits
+const MIN_MATCH: usize = 2;
+
+const HEADER_RAW: u8 = 0x00;
+pub const HEADER_COMPRESSED: u8 = 0x01;
+
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+    let mut position = 0;
+    while position < data.len() {
+        let (distance, length) = find_match(data, position);
+        if length >= MIN_MATCH {
+            writer.push(1, 0);
+            writer.push(WINDOW_BITS, distance as u32 - 1);
+            writer.push(LOOKAHEAD_BITS, length as u32 - 1);
+            position += length;
+        } else {
+            writer.push(1, 1);
+            writer.push(8, data[position] as u32);
+            position += 1;
+        }
+    }
+    return writer.finish();
+}
+
+/// The pixel bytes with the Flipper image header,
+/// compressed only if it makes the result smaller
+pub fn pack(data: &[u8]) -> Vec<u8> {
+    let compressed = compress(data);
+    if compressed.len() + 4 < data.len() + 1 {
+        let mut result = vec![HEADER_COMPRESSED, 0x00, compressed.len() as u8, (compressed.len() >> 8) as u8];
+        result.extend(compressed);
+        return result;
+    }
+    let mut result = vec![HEADER_RAW];
+    result.extend_from_slice(data);
+    return result;
+}
+
+pub fn decompress(data: &[u8]) -> Vec<u8> {
+    let mut reader = BitReader { bytes: data, position: 0 };
+    let mut result = Vec::new();
+    // the padding bits of the last byte are dropped as an incomplete back-reference
+    while let Some(tag) = reader.read(1) {
+        if tag == 1 {
+            let Some(byte) = reader.read(8) else { break };
+            result.push(byte as u8);
+            continue;
+        }
+        let (Some(distance), Some(length)) = (reader.read(WINDOW_BITS), reader.read(LOOKAHEAD_BITS)) else { break };
+        let distance = distance as usize + 1;
+        if distance > result.len() {
+            break;
+        }
+        for _ in 0..=length {
+            result.push(result[result.len() - distance]);
+        }
+    }
+    return result;
+}
+
+/// The pixel bytes of a file with the Flipper image header, None for an unknown header
+pub fn unpack(bytes: &[u8]) -> Option<Vec<u8>> {
+    match bytes.first() {
+        Some(&HEADER_RAW) => Some(bytes[1..].to_vec()),
+        Some(&HEADER_COMPRESSED) if bytes.len() >= 4 => {
+            let length = bytes[2] as usize | (bytes[3] as usize) << 8;
+            bytes.get(4..4 + length).map(decompress)
+        },
+        _ => None,
+    }
+}
+
+fn find_match(data: &[u8], position: usize) -> (usize, usize) {
+    let start = position.saturating_sub(WINDOW_SIZE);
+    let max_length = LOOKAHEAD_SIZE.min(data.len() - position);
+    let mut best = (0, 0);
+    for candidate in (start..position).rev() {
+        let mut length = 0;
+        // the source may overlap the current position, the decoder copies byte by byte
+        while length < max_length && data[candidate + length] == data[position + length] {
+            length += 1;
+        }
+        if length > best.1 {
+            best = (position - candidate, length);
+            if length == max_length {
+                break;
+            }
+        }
+    }
+    return best;
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    current: u8,
+    used: u32,
+}
+
+impl BitWriter {
+
+    fn new() -> BitWriter {
+        BitWriter { bytes: Vec::new(), current: 0, used: 0 }
+    }
+
+    // the highest bits first
+    fn push(&mut self, count: u32, value: u32) {
+        for shift in (0..count).rev() {
+            self.current = (self.current << 1) | ((value >> shift) & 1) as u8;
+            self.used += 1;
+            if self.used == 8 {
+                self.bytes.push(self.current);
+                self.current = 0;
+                self.used = 0;
+            }
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.used > 0 {
+            self.bytes.push(self.current << (8 - self.used));
+        }
+        return self.bytes;
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl BitReader<'_> {
+
+    // the highest bits first
+    fn read(&mut self, count: u32) -> Option<u32> {
+        let mut value = 0;
+        for _ in 0..count {
+            let byte = self.bytes.get(self.position / 8)?;
+            let bit = (byte >> (7 - self.position % 8)) & 1;
+            value = (value << 1) | bit as u32;
+            self.position += 1;
+        }
+        return Some(value);
+    }
+}
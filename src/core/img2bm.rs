@@ -1,31 +1,52 @@
 use std::ops::Range;
-use image::{DynamicImage, GrayImage, RgbaImage};
+use image::{DynamicImage, GrayAlphaImage, GrayImage, LumaA, RgbaImage};
 use image::imageops::FilterType;
 use crate::core::params::background::Background;
 use crate::core::bitmap::Bitmap;
 use crate::core::params::alignment::Alignment;
+use crate::core::params::dither::Dither;
+use crate::core::params::frame_params::FrameParams;
+use crate::core::color::remove_background;
+use crate::core::edges::find_edges;
+use crate::core::sharpen::sharpen;
 use crate::core::params::params::Params;
 use crate::core::params::scale_type::ScaleType;
-use crate::core::params::threshold::Threshold;
+use crate::core::params::threshold::{Threshold, ThresholdMode};
+use crate::core::auto_threshold::{histogram, local_contrast, otsu_level};
 use crate::ext::range_ext::for_each;
 use crate::ext::image_ext::Resizing;
 
 
 const MAX_RADIUS: f32 = 4.0;
 
-pub fn img2bm(image: &RgbaImage, params: &Params) -> Bitmap {
-    let resized = resize(image, params).to_luma8();
+pub fn img2bm(image: &RgbaImage, params: &Params, frame: &FrameParams) -> Bitmap {
+    let gray = to_gray(image, params, frame);
+    return gray2bm(&gray, params, frame);
+}
+
+pub fn gray2bm(gray: &GrayAlphaImage, params: &Params, frame: &FrameParams) -> Bitmap {
+    let (resized, threshold) = apply_threshold_mode(gray.clone(), &frame.threshold);
     let mut bitmap = create_bitmap(&resized, params);
-    if params.threshold.dark > 0.0 {
-        process_dark(params, &resized, &mut bitmap);
+    let previous = params.temporal.and_then(|change| frame.previous.as_ref().map(|it| (it, change)));
+    if let Some((previous, change)) = previous {
+        previous.seed(gray, &mut bitmap, change, params.inverse);
+    }
+    let fill = params.edges.is_none() || params.edges_fill;
+    if let Some(edges) = params.edges {
+        process_mask(&find_edges(&resized, edges, params.stroke), &mut bitmap);
     }
-    if !params.threshold.is_empty() {
+    if fill && threshold.dark > 0.0 {
+        process_dark(&threshold, &resized, &mut bitmap);
+    }
+    if fill && !threshold.is_empty() && params.dither != Dither::Pattern {
+        diffuse(&threshold, &resized, &mut bitmap, params.dither);
+    } else if fill && !threshold.is_empty() {
         // todo replace with sorted pixels
-        process(&params.threshold, &resized, &mut bitmap, 0.0..0.1);
-        process(&params.threshold, &resized, &mut bitmap, 0.1..0.2);
-        process(&params.threshold, &resized, &mut bitmap, 0.2..0.4);
-        process(&params.threshold, &resized, &mut bitmap, 0.4..0.65);
-        process(&params.threshold, &resized, &mut bitmap, 0.65..0.1);
+        process(&threshold, &resized, &mut bitmap, 0.0..0.1);
+        process(&threshold, &resized, &mut bitmap, 0.1..0.2);
+        process(&threshold, &resized, &mut bitmap, 0.2..0.4);
+        process(&threshold, &resized, &mut bitmap, 0.4..0.65);
+        process(&threshold, &resized, &mut bitmap, 0.65..0.1);
     }
     if params.background != Background::Invisible {
         process_outside_and_inverting(&resized, &mut bitmap, params.background);
@@ -33,10 +54,67 @@ pub fn img2bm(image: &RgbaImage, params: &Params) -> Bitmap {
     if params.inverse {
         bitmap.invert();
     }
+    if let Some((previous, change)) = previous {
+        previous.keep(gray, &mut bitmap, change);
+    }
+    return bitmap;
+}
+
+/// Pixel to pixel, without scaling and dithering, for the pictures that are already 1-bit
+pub fn pixels2bm(image: &RgbaImage, params: &Params) -> Bitmap {
+    let mut bitmap = Bitmap::with_size(image.width() as u8, image.height() as u8);
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let (pixel, visible) = params.alpha.apply(*pixel);
+        if visible && params.gray.luma(pixel.0) < 128 {
+            bitmap.set(x, y);
+        }
+    }
+    if params.inverse {
+        bitmap.invert();
+    }
     return bitmap;
 }
 
-fn create_bitmap(image: &GrayImage, params: &Params) -> Bitmap {
+/// The scaled grayscale picture the bitmap is made from,
+/// the alpha is 0 for the pixels handled like the area outside the picture and 255 for the rest
+pub fn to_gray(image: &RgbaImage, params: &Params, frame: &FrameParams) -> GrayAlphaImage {
+    let resized = to_scaled(image, params, frame);
+    let gray = GrayAlphaImage::from_fn(resized.width(), resized.height(), |x, y| {
+        let (pixel, visible) = params.alpha.apply(*resized.get_pixel(x, y));
+        LumaA([params.gray.luma(pixel.0), if visible { 255 } else { 0 }])
+    });
+    return match params.sharpen {
+        None => gray,
+        Some(kind) => sharpen(&gray, kind),
+    };
+}
+
+/// The picture without the key color background, scaled to the target size
+pub fn to_scaled(image: &RgbaImage, params: &Params, frame: &FrameParams) -> RgbaImage {
+    let resized = match frame.key {
+        None => resize(image, params),
+        Some(key) => {
+            let mut image = image.clone();
+            remove_background(&mut image, key.color.unwrap(), key.tolerance, params.key_visible);
+            resize(&image, params)
+        },
+    };
+    return resized.to_rgba8();
+}
+
+fn apply_threshold_mode(resized: GrayAlphaImage, threshold: &Threshold) -> (GrayAlphaImage, Threshold) {
+    match threshold.mode {
+        ThresholdMode::Fixed => (resized, threshold.clone()),
+        ThresholdMode::Otsu => {
+            let level = otsu_level(&histogram(&resized));
+            (resized, threshold.around(level))
+        },
+        ThresholdMode::Mean(radius) => (local_contrast(&resized, radius, false), threshold.around(0.5)),
+        ThresholdMode::Gaussian(radius) => (local_contrast(&resized, radius, true), threshold.around(0.5)),
+    }
+}
+
+fn create_bitmap(image: &GrayAlphaImage, params: &Params) -> Bitmap {
     let mut dx = 0;
     if params.alignment != Alignment::Left {
         dx = image.width() as i32 - params.width as i32;
@@ -54,16 +132,29 @@ fn create_bitmap(image: &GrayImage, params: &Params) -> Bitmap {
     return Bitmap::new(params.width, params.height, dx, dy);
 }
 
-fn process_dark(params: &Params, resized: &GrayImage, bitmap: &mut Bitmap) {
+fn process_dark(threshold: &Threshold, resized: &GrayAlphaImage, bitmap: &mut Bitmap) {
     for_each_luminance(resized, bitmap, |bitmap, x, y, outside, luminance| {
-        if !outside && luminance < params.threshold.dark {
+        if !outside && luminance < threshold.dark {
+            bitmap.set(x, y);
+        }
+    });
+}
+
+fn process_mask(mask: &GrayImage, bitmap: &mut Bitmap) {
+    for_each(0..bitmap.height as u32, 0..bitmap.width as u32, |x, y| {
+        let src_x = bitmap.get_src_x(x);
+        let src_y = bitmap.get_src_y(y);
+        if src_x < 0 || src_x >= mask.width() as i32 || src_y < 0 || src_y >= mask.height() as i32 {
+            return;
+        }
+        if mask.get_pixel(src_x as u32, src_y as u32).0[0] != 0 {
             bitmap.set(x, y);
         }
     });
 }
 
 fn process_outside_and_inverting(
-    resized: &GrayImage,
+    resized: &GrayAlphaImage,
     bitmap: &mut Bitmap,
     background: Background,
 ) {
@@ -86,7 +177,7 @@ fn process_outside_and_inverting(
     });
 }
 
-fn process(threshold: &Threshold, resized: &GrayImage, bitmap: &mut Bitmap, range: Range<f32>) {
+fn process(threshold: &Threshold, resized: &GrayAlphaImage, bitmap: &mut Bitmap, range: Range<f32>) {
     for_each_luminance(resized, bitmap, |bitmap, x, y, outside, luminance| {
         if !outside && threshold.contains(luminance) {
             let luminance = (luminance - threshold.dark) / threshold.size();
@@ -101,8 +192,32 @@ fn process(threshold: &Threshold, resized: &GrayImage, bitmap: &mut Bitmap, rang
     });
 }
 
+// the pixels within the threshold are dithered as the brightness from 0 at the dark to 1 at the light value
+fn diffuse(threshold: &Threshold, resized: &GrayAlphaImage, bitmap: &mut Bitmap, dither: Dither) {
+    let width = bitmap.width as i32;
+    let height = bitmap.height as i32;
+    let mut errors = vec![0f32; (width * height) as usize];
+    for_each_luminance(resized, bitmap, |bitmap, x, y, outside, luminance| {
+        if outside || !threshold.contains(luminance) {
+            return;
+        }
+        let value = (luminance - threshold.dark) / threshold.size() + errors[(y as i32 * width + x as i32) as usize];
+        let dark = value < 0.5;
+        if dark {
+            bitmap.set(x, y);
+        }
+        let error = if dark { value } else { value - 1.0 };
+        for &(dx, dy, part) in dither.kernel() {
+            let (x, y) = (x as i32 + dx, y as i32 + dy);
+            if x >= 0 && x < width && y < height {
+                errors[(y * width + x) as usize] += error * part;
+            }
+        }
+    });
+}
+
 fn for_each_luminance<F>(
-    image: &GrayImage,
+    image: &GrayAlphaImage,
     bitmap: &mut Bitmap,
     mut action: F,
 ) where F: FnMut(&mut Bitmap, u32, u32, /*outside:*/bool, /*luminance:*/f32) {
@@ -116,8 +231,8 @@ fn for_each_luminance<F>(
             action(bitmap, x, y, true, 0.0);
             return;
         }
-        let luminance = image.get_pixel(src_x as u32, src_y as u32).0[0] as f32 / 255.0;
-        action(bitmap, x, y, false, luminance);
+        let [luminance, alpha] = image.get_pixel(src_x as u32, src_y as u32).0;
+        action(bitmap, x, y, alpha == 0, luminance as f32 / 255.0);
     });
 }
 
@@ -0,0 +1,177 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::path::Path;
+use serde::Serialize;
+use crate::core::asset_pack::{size_of, ICON_META_FILE};
+use crate::core::bitmap::Bitmap;
+use crate::core::dolphin::{parse_manifest, parse_meta, MANIFEST_FILE, META_FILE};
+use crate::core::heatshrink::{unpack, HEADER_COMPRESSED};
+use crate::core::meta::FrameData;
+use crate::core::terminal::half_blocks;
+use crate::ext::path_ext::{EXT_BM, EXT_BMX};
+
+
+const THUMBNAIL_WIDTH: u32 = 64;
+
+#[derive(Serialize)]
+pub struct Info {
+    pub path: String,
+    /// bitmap, icon, animation or animated icon
+    pub kind: &'static str,
+    pub width: u32,
+    pub height: u32,
+    /// none, heatshrink or mixed
+    pub compression: &'static str,
+    pub unique_frames: usize,
+    pub order_length: usize,
+    pub frame_rate: Option<u32>,
+    pub duration_ms: Option<u32>,
+    /// The files of the frames as they are stored
+    pub flash_bytes: usize,
+    /// The frames are kept in RAM as they are stored, the compressed ones are unpacked into a frame buffer to be drawn
+    pub ram_bytes: usize,
+    #[serde(skip)]
+    pub thumbnail: Option<Bitmap>,
+}
+
+impl Display for Info {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.path)?;
+        writeln!(f, "  {} {}x{}, compression: {}", self.kind, self.width, self.height, self.compression)?;
+        write!(f, "  {} unique frames, {} in the order", self.unique_frames, self.order_length)?;
+        if let (Some(frame_rate), Some(duration)) = (self.frame_rate, self.duration_ms) {
+            write!(f, ", {frame_rate} fps, {duration} ms")?;
+        }
+        writeln!(f)?;
+        writeln!(f, "  flash {} bytes, RAM about {} bytes", self.flash_bytes, self.ram_bytes)?;
+        if let Some(thumbnail) = &self.thumbnail {
+            let step = (thumbnail.width as u32).div_ceil(THUMBNAIL_WIDTH);
+            write!(f, "{}", half_blocks(thumbnail, step))?;
+        }
+        return Ok(());
+    }
+}
+
+/// The info of a .bm or .bmx file, of an animation or animated icon folder, or of every animation of a dolphin directory
+pub fn info(path: &Path) -> Result<Vec<Info>, String> {
+    if path.join(MANIFEST_FILE).is_file() {
+        let text = fs::read_to_string(path.join(MANIFEST_FILE)).map_err(|it| it.to_string())?;
+        return parse_manifest(&text)?.iter()
+            .map(|entry| anim_info(&path.join(&entry.name)))
+            .collect();
+    }
+    let info = match () {
+        _ if path.join(META_FILE).is_file() => anim_info(path)?,
+        _ if path.join(ICON_META_FILE).is_file() => animated_icon_info(path)?,
+        _ if path.is_dir() => return Err(String::from("neither a dolphin, an animation nor an animated icon folder")),
+        _ => file_info(path)?,
+    };
+    return Ok(vec![info]);
+}
+
+fn file_info(path: &Path) -> Result<Info, String> {
+    let bytes = read(path)?;
+    let ext = path.extension().map(|it| it.to_string_lossy().to_lowercase()).unwrap_or_default();
+    if ext == EXT_BMX {
+        let size = |at: usize| bytes.get(at..at + 4).map(|it| u32::from_le_bytes(it.try_into().unwrap()));
+        let (Some(width), Some(height)) = (size(0), size(4)) else { return Err(String::from("no size")) };
+        let frames = vec![bytes[8..].to_vec()];
+        return Ok(from_frames(path, "icon", width, height, &frames, &[0], None));
+    }
+    if ext != EXT_BM {
+        return Err(String::from("neither a .bm nor .bmx file"));
+    }
+    let name = path.file_stem().unwrap_or_default().to_string_lossy();
+    let meta = path.parent()
+        .and_then(|it| fs::read_to_string(it.join(META_FILE)).ok())
+        .and_then(|it| parse_meta(&it).ok());
+    let (width, height) = match (meta, size_of(&name)) {
+        (Some(meta), _) => (meta.width, meta.height),
+        (None, Some((width, height))) => (width as u32, height as u32),
+        _ => return Err(String::from("unknown size, the file is neither next to meta.txt nor named like icon_10x10.bm")),
+    };
+    return Ok(from_frames(path, "bitmap", width, height, &[bytes], &[0], None));
+}
+
+fn anim_info(dir: &Path) -> Result<Info, String> {
+    let text = fs::read_to_string(dir.join(META_FILE)).map_err(|it| format!("{}: {it}", dir.display()))?;
+    let meta = parse_meta(&text).map_err(|it| format!("{}: {it}", dir.display()))?;
+    if meta.frame_rate == 0 {
+        return Err(format!("{}: the frame rate is 0", dir.display()));
+    }
+    let unique = meta.unique_frames();
+    let frames = unique.iter()
+        .map(|index| read(&dir.join(format!("frame_{index}.{EXT_BM}"))))
+        .collect::<Result<Vec<Vec<u8>>, String>>()?;
+    let order = meta.frames_order.iter()
+        .map(|it| unique.iter().position(|unique| unique == it).unwrap())
+        .collect::<Vec<usize>>();
+    return Ok(from_frames(dir, "animation", meta.width, meta.height, &frames, &order, Some(meta.frame_rate)));
+}
+
+fn animated_icon_info(dir: &Path) -> Result<Info, String> {
+    let meta = read(&dir.join(ICON_META_FILE))?;
+    let values = meta.chunks_exact(4)
+        .map(|it| u32::from_le_bytes(it.try_into().unwrap()))
+        .collect::<Vec<u32>>();
+    let &[width, height, frame_rate, frame_count] = values.as_slice() else {
+        return Err(format!("{}: the meta isn't 4 numbers", dir.display()));
+    };
+    if frame_rate == 0 {
+        return Err(format!("{}: the frame rate is 0", dir.display()));
+    }
+    let frames = (0..frame_count)
+        .map(|index| read(&dir.join(format!("frame_{index:02}.{EXT_BM}"))))
+        .collect::<Result<Vec<Vec<u8>>, String>>()?;
+    let order = (0..frames.len()).collect::<Vec<usize>>();
+    return Ok(from_frames(dir, "animated icon", width, height, &frames, &order, Some(frame_rate)));
+}
+
+/// The order refers to the frames, the frame rate is None for a still picture
+fn from_frames(
+    path: &Path,
+    kind: &'static str,
+    width: u32,
+    height: u32,
+    frames: &[Vec<u8>],
+    order: &[usize],
+    frame_rate: Option<u32>,
+) -> Info {
+    let compressed = frames.iter().filter(|it| it.first() == Some(&HEADER_COMPRESSED)).count();
+    let compression = match compressed {
+        0 => "none",
+        _ if compressed == frames.len() => "heatshrink",
+        _ => "mixed",
+    };
+    let flash_bytes = frames.iter().map(|it| it.len()).sum::<usize>();
+    let frame_buffer = (width as usize).div_ceil(8) * height as usize;
+    let ram_bytes = flash_bytes + if compressed > 0 { frame_buffer } else { 0 };
+    let played = frame_rate.map(|frame_rate| {
+        order.iter()
+            .map(|&index| FrameData { index, duration: 1000.0 / frame_rate as f32 })
+            .collect::<Vec<FrameData>>()
+    });
+    let duration_ms = played.as_ref().map(|it| it.iter().map(|it| it.duration).sum::<f32>().round() as u32);
+    let thumbnail = order.first()
+        .and_then(|&it| unpack(&frames[it]))
+        .filter(|_| width <= u8::MAX as u32 && height <= u8::MAX as u32)
+        .map(|data| Bitmap::from_data(width as u8, height as u8, &data));
+    return Info {
+        path: path.display().to_string(),
+        kind,
+        width,
+        height,
+        compression,
+        unique_frames: frames.len(),
+        order_length: played.map_or(1, |it| it.len()),
+        frame_rate,
+        duration_ms,
+        flash_bytes,
+        ram_bytes,
+        thumbnail,
+    };
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|it| format!("{}: {it}", path.display()))
+}
@@ -1,4 +1,5 @@
 use image::Delay;
+use crate::core::params::recipe::{ManifestValues, Recipe};
 use crate::ext::iter_ext::Sum;
 
 pub struct FrameData {
@@ -13,8 +14,8 @@ impl FrameData {
     }
 }
 
-pub fn get_meta(height: u8, frames: &Vec<FrameData>) -> String {
-    let duration = frames.iter().sum_of(0f32, |it| it.duration) as usize;
+/// The frame indexes repeated by their durations and the frame rate they are played with
+pub fn get_order(frames: &Vec<FrameData>) -> (Vec<usize>, u32) {
     let min_dur = frames.iter()
         .min_by(|&f,&s| f.duration.partial_cmp(&s.duration).unwrap())
         .unwrap()
@@ -27,9 +28,32 @@ pub fn get_meta(height: u8, frames: &Vec<FrameData>) -> String {
         }
     }
     let frame_rate = (1000.0 / min_dur) as u32;
-    let a_frames = frames.iter()
-        .max_of(0, |it| it.index) + 1;
+    return (order, frame_rate);
+}
+
+pub fn get_meta(width: u8, height: u8, frames: &Vec<FrameData>, recipe: &Recipe) -> String {
+    let duration = frames.iter().sum_of(0f32, |it| it.duration) as usize;
+    let (order, frame_rate) = get_order(frames);
+    let a_frames = match recipe.animation.passive_frames {
+        Some(passive) => order.len() - passive.min(order.len()),
+        None => frames.iter().max_of(0, |it| it.index) + 1,
+    };
     let p_frames = order.len() - a_frames;
+    let active_cycles = recipe.animation.active_cycles.unwrap_or(1);
+    let active_cooldown = recipe.animation.active_cooldown;
+    let bubble_slots = recipe.bubbles.iter().map(|it| it.slot + 1).max().unwrap_or(0);
+    let bubbles = recipe.bubbles.iter()
+        .map(|it| format!("
+Slot: {}
+X: {}
+Y: {}
+Text: {}
+AlignH: {}
+AlignV: {}
+StartFrame: {}
+EndFrame: {}
+", it.slot, it.x, it.y, it.text, it.align_h, it.align_v, it.start_frame, it.end_frame))
+        .collect::<String>();
     let order = order.iter()
         .map(|it| it.to_string())
         .collect::<Vec<String>>()
@@ -37,29 +61,29 @@ pub fn get_meta(height: u8, frames: &Vec<FrameData>) -> String {
     return format!("Filetype: Flipper Animation
 Version: 1
 
-Width: 128
+Width: {width}
 Height: {height}
 Passive frames: {p_frames}
 Active frames: {a_frames}
 Frames order: {order}
-Active cycles: 1
+Active cycles: {active_cycles}
 Frame rate: {frame_rate}
 Duration: {duration}
-Active cooldown: 0
+Active cooldown: {active_cooldown}
 
-Bubble slots: 0
-")
+Bubble slots: {bubble_slots}
+{bubbles}")
 }
 
-pub fn get_manifest(with_header: bool, name: String) -> String {
+pub fn get_manifest(with_header: bool, name: String, values: &ManifestValues) -> String {
     let header = if with_header { "Filetype: Flipper Animation Manifest\nVersion: 1" } else { "" };
     return format!("{header}
 
 Name: {name}
-Min butthurt: 0
-Max butthurt: 13
-Min level: 1
-Max level: 3
-Weight: 8
-")
+Min butthurt: {}
+Max butthurt: {}
+Min level: {}
+Max level: {}
+Weight: {}
+", values.min_butthurt, values.max_butthurt, values.min_level, values.max_level, values.weight)
 }
@@ -3,6 +3,24 @@ mod values;
 pub mod params;
 pub mod scale_type;
 pub mod threshold;
+pub mod dither;
 pub mod frame_cut;
 pub mod background;
 pub mod alignment;
+pub mod grayscale;
+pub mod alpha;
+pub mod edges;
+pub mod sharpen;
+pub mod text_align;
+pub mod font_source;
+pub mod c_array;
+pub mod output_format;
+pub mod layout;
+pub mod key_color;
+pub mod frame_params;
+pub mod task;
+pub mod config;
+pub mod recipe;
+pub mod term_preview;
+pub mod preview_theme;
+pub mod sweep;
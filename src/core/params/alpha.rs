@@ -0,0 +1,82 @@
+use std::fmt::{Debug, Display, Formatter};
+use image::Rgba;
+use crate::core::color::{color_to_u32, Color};
+
+
+const WHITE: Color = Color { r: 255, g: 255, b: 255 };
+const DEFAULT_MASK_LEVEL: u8 = 50;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Alpha {
+    /// The alpha channel is dropped, the stored color is used as is
+    Ignore,
+    /// Fully transparent pixels are handled like the area outside the picture,
+    /// semi-transparent ones are composited over white
+    Background,
+    /// All pixels are composited over the color
+    Matte(Color),
+    /// Pixels with the alpha below the percentage are handled like the area outside the picture,
+    /// the rest are fully opaque
+    Mask(u8),
+}
+
+impl Alpha {
+
+    /// The pixel with the color to take the luminance from and whether it isn't a background,
+    /// the alpha value is kept for the alpha grayscale conversion
+    pub fn apply(&self, pixel: Rgba<u8>) -> (Rgba<u8>, bool) {
+        let alpha = pixel[3];
+        match *self {
+            Alpha::Ignore => (pixel, true),
+            Alpha::Background => (compose(pixel, WHITE), alpha > 0),
+            Alpha::Matte(color) => (compose(pixel, color), true),
+            Alpha::Mask(level) => (pixel, alpha as u32 * 100 >= level as u32 * 255),
+        }
+    }
+}
+
+fn compose(pixel: Rgba<u8>, matte: Color) -> Rgba<u8> {
+    let alpha = pixel[3] as u32;
+    let blend = |value: u8, matte: u8| ((value as u32 * alpha + matte as u32 * (255 - alpha) + 127) / 255) as u8;
+    Rgba([blend(pixel[0], matte.r), blend(pixel[1], matte.g), blend(pixel[2], matte.b), pixel[3]])
+}
+
+impl Display for Alpha {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Alpha::Ignore => write!(f, "ignore"),
+            Alpha::Background => write!(f, "background"),
+            Alpha::Matte(Color { r, g, b }) => write!(f, "matte:{r:02x}{g:02x}{b:02x}"),
+            Alpha::Mask(level) => write!(f, "mask:{level}"),
+        }
+    }
+}
+
+impl Debug for Alpha {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::str::FromStr for Alpha {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, value) = s.split_once(':').unwrap_or((s, ""));
+        let variant = match name {
+            "ignore" if value.is_empty() => Alpha::Ignore,
+            "background" if value.is_empty() => Alpha::Background,
+            "matte" if value.is_empty() => Alpha::Matte(WHITE),
+            "matte" => Alpha::Matte(Color::parse(color_to_u32(value)?)),
+            "mask" if value.is_empty() => Alpha::Mask(DEFAULT_MASK_LEVEL),
+            "mask" => {
+                let level = value.parse::<u8>().ok()
+                    .filter(|&it| it <= 100)
+                    .ok_or(format!("'{value}' isn't a valid percentage"))?;
+                Alpha::Mask(level)
+            },
+            _ => return Err(format!("invalid variant: {s}")),
+        };
+        return Ok(variant);
+    }
+}
@@ -1,29 +1,64 @@
 use std::fmt::Debug;
 use std::path::PathBuf;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use crate::core::params::alignment::Alignment;
 use crate::core::params::background::Background;
+use crate::core::params::dither::Dither;
 use crate::core::params::frame_cut::FrameCut;
+use crate::core::params::grayscale::Grayscale;
+use crate::core::params::alpha::Alpha;
+use crate::core::params::edges::Edges;
+use crate::core::params::sharpen::Sharpen;
+use crate::core::params::text_align::TextAlign;
+use crate::core::params::font_source::FontSource;
+use crate::core::params::term_preview::TermPreview;
+use crate::core::params::preview_theme::PreviewTheme;
+use crate::core::params::c_array::CArray;
+use crate::core::params::output_format::OutputFormat;
+use crate::core::params::layout::Layout;
+use crate::core::params::key_color::{KeyColor, DEFAULT_KEY_TOLERANCE};
+use crate::core::color::{color_to_u32, Color};
 use crate::core::params::values::Values;
 use crate::core::params::scale_type::ScaleType;
-use crate::core::params::threshold::Threshold;
+use crate::core::params::sweep::Sweep;
+use crate::core::params::threshold::{Threshold, ThresholdMode};
 
-#[derive(Debug, Parser)]
+#[derive(Debug, Clone, Parser)]
 
 #[command(name = "img2fbm")]
 #[command(author = "Nesterov Y. <atomofiron@gmail.com>")]
 #[command(version = "1.0")]
 #[command(about = "Flipper bitmap files generator", long_about = None)]
 #[command(arg_required_else_help = true)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
+#[command(args_override_self = true)]
 pub struct Cli {
-    /// Path to png|jpg|jpeg|gif file
-    #[arg(value_name = "source")]
-    pub source_path: PathBuf,
+    /// The passed arguments with the binary name, without the options of the config files and the recipe
+    #[arg(skip)]
+    pub args: Vec<String>,
 
-    /// Path to the 'dolphin' directory, if the GIF passed
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Path to png|jpg|jpeg|gif file, or to a folder of the firmware PNG frames to convert into bm
+    #[arg(value_name = "source", required = true)]
+    pub source_path: Option<PathBuf>,
+
+    /// Path to the 'dolphin' directory, if the GIF or APNG passed.
+    /// A folder of frames is converted into <folder>_bm next to it without one
     #[arg(value_name = "dolphin")]
     pub dolphin_path: Option<PathBuf>,
 
+    /// Apply the options of the [presets.<name>] table of img2fbm.toml, the passed options override them.
+    /// The img2fbm.toml of the current or a parent directory and of the user config directory set the default options
+    #[arg(long, value_name = "name")]
+    pub preset: Option<String>,
+
+    /// Convert again on every change of the source, its recipe, the config files, the scene and the font,
+    /// until Ctrl+C is pressed
+    #[arg(long)]
+    pub watch: bool,
+
     /// Sets the height of output frame(s)
     #[arg(
         required = false,
@@ -51,10 +86,44 @@ pub struct Cli {
     #[arg(long = "op")]
     pub only_preview: bool,
 
+    /// Draw the result in the terminal, GIF frames are played at their frame rate
+    #[arg(long = "term-preview", value_name = "mode", num_args = 0..=1, default_missing_value = "auto")]
+    pub term_preview: Option<TermPreview>,
+
     /// Preview scale ratio
     #[arg(long = "ps", default_value_t = 3, value_name = "multiplier")]
     pub preview_scale: u8,
 
+    /// Colors of the preview pictures
+    #[arg(long = "preview-theme", value_name = "theme", default_value = "plain")]
+    pub preview_theme: PreviewTheme,
+
+    /// Separate the preview pixels with the grid lines, as the LCD has, with the preview scale of 2 and more
+    #[arg(long = "pixel-grid")]
+    pub pixel_grid: bool,
+
+    /// Show the preview on the whole 128x64 screen in the bezel, at the bottom and under the status bar as the dolphin is
+    #[arg(long)]
+    pub screen: bool,
+
+    /// Save the scaled source in color and grayscale next to the result with the parameters, for GIF frame by frame
+    #[arg(long)]
+    pub compare: bool,
+
+    /// Save the results of the thresholds next to the source in one picture, the first frame for GIF.
+    /// A range such as 20:80 is dithered, a single value such as 50 is not
+    #[arg(long = "contact-sheet", value_name = "threshold,...", value_parser = str_to_threshold, value_delimiter = ',')]
+    pub contact_sheet: Vec<Threshold>,
+
+    /// Convert with every combination of the option values and save the results labeled in one picture, or GIF for GIF,
+    /// such as --sweep threshold=10:60,20:80 --sweep dither=fs,atkinson. The option is the long name without the dashes
+    #[arg(long, value_name = "option=value,...", value_parser = str_to_sweep)]
+    pub sweep: Vec<Sweep>,
+
+    /// Also write the output files of every sweep combination, named with its values
+    #[arg(long = "sweep-files")]
+    pub sweep_files: bool,
+
     /// Inverse output pixels
     #[arg(short, long)]
     pub inverse: bool,
@@ -66,14 +135,142 @@ pub struct Cli {
     /// Set background pixels visible
     #[arg(short, long, value_name = "background", default_value = "invisible")]
     pub background: Background,
-    // thread 'main' has overflowed its stack
-    // fatal runtime error: stack overflow
-    // caused by default_value_t = Background::Invisible
 
-    /// Threshold value or range of pixel brightness as a percentage, such as 20:80, 40:, :60, 50:50 or 50
+    /// Threshold value or range of pixel brightness as a percentage, such as 20:80, 40:, :60, 50:50 or 50.
+    /// Also auto[:range] to find the level by Otsu's method,
+    /// mean[:radius[:range]] or gaussian[:radius[:range]] for a local adaptive threshold, such as auto:30 or mean:8:20
     #[arg(short, long, value_name = "percentage[:percentage]", value_parser = str_to_threshold, default_value = "20:80")]
     pub threshold: Threshold,
 
+    /// Dithering of the pixels within the threshold range
+    #[arg(long, value_name = "kind", default_value = "pattern")]
+    pub dither: Dither,
+
+    /// Grayscale conversion: luminance, average, lightness, red, green, blue, alpha
+    /// or custom red:green:blue weights, such as 2:1:1. With alpha opaque pixels are dark
+    #[arg(short, long, value_name = "conversion", default_value = "luminance")]
+    pub gray: Grayscale,
+
+    /// Transparency handling: background to treat transparent pixels like the area outside the picture,
+    /// matte[:RRGGBB] to composite over the color (white by default),
+    /// mask[:percentage] to treat pixels with the lower alpha as background, or ignore
+    #[arg(long, value_name = "policy", default_value = "background")]
+    pub alpha: Alpha,
+
+    /// Find the auto threshold for every GIF frame separately instead of once for the whole GIF
+    #[arg(long = "frame-threshold")]
+    pub frame_threshold: bool,
+
+    /// Sharpen the scaled picture before the thresholding: unsharp[:radius[:amount]]
+    /// or laplacian[:amount], the amount is a percentage, such as unsharp:2:150 or laplacian:50
+    #[arg(long, value_name = "kind")]
+    pub sharpen: Option<Sharpen>,
+
+    /// Render edges instead of filled tones: sobel[:percentage], canny[:low:high]
+    /// or outline of the non-background silhouette, such as sobel:40 or canny:10:30
+    #[arg(short, long, value_name = "kind")]
+    pub edges: Option<Edges>,
+
+    /// Edge stroke width in pixels
+    #[arg(
+        long,
+        value_name = "1-16",
+        value_parser = clap::value_parser!(u8).range(1..=16),
+        default_value_t = 1,
+    )]
+    pub stroke: u8,
+
+    /// Draw the edges over the threshold fill instead of the blank
+    #[arg(long = "edges-fill")]
+    pub edges_fill: bool,
+
+    /// Remove the solid background of the color with a channel tolerance 0-255, such as ffffff, 00ff00:80,
+    /// auto or auto:30 to take the color from the picture corners
+    #[arg(short, long = "key-color", alias = "key", value_name = "RRGGBB[:tolerance]", value_parser = str_to_key_color)]
+    pub key_color: Option<KeyColor>,
+
+    /// Make the removed background visible instead of transparent
+    #[arg(long = "key-visible")]
+    pub key_visible: bool,
+
+    /// Take the auto key color from every GIF frame separately instead of the first one
+    #[arg(long = "frame-key")]
+    pub frame_key: bool,
+
+    /// Path to a still png|jpg|jpeg picture to composite every source frame with
+    #[arg(long, value_name = "picture")]
+    pub scene: Option<PathBuf>,
+
+    /// Position of the source frames on the scene in the scene pixels, such as 40:12 or -8:0
+    #[arg(long = "at", value_name = "x:y", value_parser = str_to_position, default_value = "0:0", allow_hyphen_values = true)]
+    pub scene_position: (i64, i64),
+
+    /// Draw the scene above the source frames instead of below, the frames are visible through its transparent areas
+    #[arg(long = "scene-above")]
+    pub scene_above: bool,
+
+    /// Remove the solid background of the source frames before the compositing, such as 00ff00:80 or auto
+    #[arg(long = "sprite-key", value_name = "RRGGBB[:tolerance]", value_parser = str_to_key_color)]
+    pub sprite_key: Option<KeyColor>,
+
+    /// Text to draw over the output frame(s), %n is replaced with the frame number.
+    /// Pass it several times to take the texts for the frames one by one in a cycle
+    #[arg(long, value_name = "text")]
+    pub text: Vec<String>,
+
+    /// Font of the text: primary, secondary, keyboard or big-numbers of the Flipper firmware,
+    /// or a path to a BDF font file
+    #[arg(long, value_name = "font|path", default_value = "secondary")]
+    pub font: FontSource,
+
+    /// Position of the text top in the output pixels, such as 2:54
+    #[arg(long = "text-at", value_name = "x:y", value_parser = str_to_position, default_value = "0:0", allow_hyphen_values = true)]
+    pub text_position: (i64, i64),
+
+    /// Horizontal text alignment relative to the position
+    #[arg(long = "text-align", value_name = "side", default_value = "left")]
+    pub text_align: TextAlign,
+
+    /// Draw the text cleared over a visible box
+    #[arg(long = "text-box")]
+    pub text_box: bool,
+
+    /// Scroll the text from right to left across the whole width by the pixels per frame
+    #[arg(long, value_name = "pixels")]
+    pub marquee: Option<u32>,
+
+    /// Output file format, the Flipper Animation files are written only with bm and png
+    #[arg(short, long, value_name = "format", default_value = "bm")]
+    pub format: OutputFormat,
+
+    /// Build an asset pack in the directory from the source folder: its Icons/<Category>/<Name>_<W>x<H> and Anims/<Name>
+    /// pictures and animations, or the targets.txt lines such as 'Icons/Passport/passport_happy_46x49 = happy.png'.
+    /// The targets with the same source, recipe, scene and font files and options are skipped, the ones removed from the source are removed.
+    /// Remove .img2fbm_cache of the pack to build it all
+    #[arg(long = "asset-pack", value_name = "pack")]
+    pub asset_pack: Option<PathBuf>,
+
+    /// Byte layout of the raw format and the C arrays, any other than horizontal-lsb makes the C arrays headerless
+    #[arg(long, value_name = "layout", default_value = "horizontal-lsb")]
+    pub layout: Layout,
+
+    /// Also write the frame(s) as C arrays into <source>.h and <source>.c files
+    #[arg(long = "c-array", value_name = "kind")]
+    pub c_array: Option<CArray>,
+
+    /// Add the Icon struct of the firmware assets_icons layout to the C files
+    #[arg(long = "c-icon")]
+    pub c_icon: bool,
+
+    /// Keep the previous GIF frame pixels where the brightness changed less than the percentage,
+    /// so the dithering doesn't flicker in the static areas, such as 5
+    #[arg(
+        long,
+        value_name = "percentage",
+        value_parser = clap::value_parser!(u8).range(0..=100),
+    )]
+    pub temporal: Option<u8>,
+
     /// Animation speed ratio
     #[arg(short, long, value_name = "speed", default_value_t = 1.0, value_parser = str_to_speed)]
     pub speed: f32,
@@ -83,14 +280,98 @@ pub struct Cli {
     pub cut: FrameCut,
 }
 
+const DEFAULT_ADAPTIVE_RADIUS: u32 = 8;
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Validate the manifest.txt of a 'dolphin' directory and the animations it refers to
+    Check {
+        /// Path to the 'dolphin' directory
+        #[arg(value_name = "dolphin")]
+        dolphin_path: PathBuf,
+    },
+    /// Print the size, frames, frame rate and footprint of a .bm or .bmx file, an animation or a 'dolphin' directory
+    Info {
+        /// Path to the file or directory
+        #[arg(value_name = "path")]
+        path: PathBuf,
+        /// Print JSON instead of the text with a thumbnail
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+impl Cli {
+
+    /// The source is required unless a command is passed
+    pub fn source(&self) -> &PathBuf {
+        self.source_path.as_ref().expect("no source")
+    }
+}
+
 fn str_to_threshold(value: &str) -> Result<Threshold, String> {
+    let (name, rest) = value.split_once(':').unwrap_or((value, ""));
+    let (mode, range) = match name {
+        "auto" => (ThresholdMode::Otsu, rest),
+        "mean" | "gaussian" => {
+            let (radius, range) = rest.split_once(':').unwrap_or((rest, ""));
+            let radius = match radius {
+                "" => DEFAULT_ADAPTIVE_RADIUS,
+                _ => radius.parse::<u32>().map_err(|_| format!("'{radius}' isn't a valid radius"))?,
+            };
+            match name {
+                "mean" => (ThresholdMode::Mean(radius), range),
+                _ => (ThresholdMode::Gaussian(radius), range),
+            }
+        },
+        _ => (ThresholdMode::Fixed, value),
+    };
+    if mode != ThresholdMode::Fixed {
+        let range = match range {
+            "" => 0,
+            _ => range.parse::<u8>().ok().filter(|&it| it <= 100).ok_or(format!("'{range}' isn't a valid range"))?,
+        };
+        let half = range as f32 / 200.0;
+        return Ok(Threshold { dark: 0.5 - half, light: 0.5 + half, mode });
+    }
     let from_to = Values::<u8>::from::<u8>(value, 0, 100)?;
     if from_to.first > from_to.second {
         panic!("The first value must be greater than the second value")
     }
     let dark = from_to.first as f32 / 100.0;
     let light = from_to.second as f32 / 100.0;
-    return Ok(Threshold { dark, light });
+    return Ok(Threshold { dark, light, mode });
+}
+
+fn str_to_sweep(value: &str) -> Result<Sweep, String> {
+    let (option, values) = value.split_once('=').ok_or(format!("'{value}' isn't option=value,..."))?;
+    let option = option.trim().trim_start_matches('-').to_string();
+    let values = values.split(',')
+        .map(|it| it.trim().to_string())
+        .filter(|it| !it.is_empty())
+        .collect::<Vec<String>>();
+    if option.is_empty() || values.is_empty() {
+        return Err(format!("'{value}' isn't option=value,..."));
+    }
+    return Ok(Sweep { option, values });
+}
+
+fn str_to_key_color(value: &str) -> Result<KeyColor, String> {
+    let (color, tolerance) = value.split_once(':').unwrap_or((value, ""));
+    let tolerance = match tolerance {
+        "" => DEFAULT_KEY_TOLERANCE,
+        _ => tolerance.parse::<u8>().map_err(|_| format!("'{tolerance}' isn't a valid tolerance"))?,
+    };
+    let color = match color {
+        "auto" => None,
+        _ => Some(Color::parse(color_to_u32(color)?)),
+    };
+    return Ok(KeyColor { color, tolerance });
+}
+
+fn str_to_position(value: &str) -> Result<(i64, i64), String> {
+    let x_y = Values::<i64>::from::<i64>(value, 0, 0)?;
+    return Ok((x_y.first, x_y.second));
 }
 
 fn str_to_frame_cut(value: &str) -> Result<FrameCut, String> {
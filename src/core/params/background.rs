@@ -24,7 +24,7 @@ impl ValueEnum for Background {
 
 impl Display for Background {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        write!(f, "{}", self.to_possible_value().unwrap().get_name())
     }
 }
 
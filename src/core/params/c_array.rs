@@ -0,0 +1,46 @@
+use std::fmt::{Debug, Display, Formatter};
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CArray {
+    Raw, Compressed
+}
+
+impl ValueEnum for CArray {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[CArray::Raw, CArray::Compressed]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+        Some(match self {
+            CArray::Raw => PossibleValue::new("raw").help("Uncompressed bytes with the 0x00 header"),
+            CArray::Compressed => PossibleValue::new("compressed").help("Heatshrink compressed bytes if it's smaller, like the firmware assets"),
+        })
+    }
+}
+
+impl Display for CArray {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_possible_value().unwrap().get_name())
+    }
+}
+
+impl Debug for CArray {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::str::FromStr for CArray {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        for variant in Self::value_variants() {
+            if variant.to_possible_value().unwrap().matches(s, false) {
+                return Ok(*variant);
+            }
+        }
+        Err(format!("invalid variant: {s}"))
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use clap::{Arg, ArgAction, Command, CommandFactory, Error, FromArgMatches};
+use clap::error::ErrorKind::InvalidValue;
+use clap::parser::ValueSource;
+use toml::{Table, Value};
+use crate::core::params::args::Cli;
+use crate::core::params::recipe::Recipe;
+
+
+pub const CONFIG_FILE: &str = "img2fbm.toml";
+const APP_DIR: &str = "img2fbm";
+const PRESETS: &str = "presets";
+const PRESET_OPTION: &str = "preset";
+const SOURCE_ID: &str = "source_path";
+const NEGATION_PREFIX: &str = "no-";
+
+/// The option values of the user and project config files, as the long option names and their values
+#[derive(Default)]
+pub struct Config {
+    defaults: Vec<(String, Value)>,
+    presets: HashMap<String, Vec<(String, Value)>>,
+}
+
+impl Config {
+
+    /// The user config overridden by the config of the current directory or the closest parent one
+    pub fn load() -> Result<Config, Error> {
+        let mut config = Config::default();
+        for path in config_paths() {
+            config.add(&path)?;
+        }
+        return Ok(config);
+    }
+
+    fn add(&mut self, path: &Path) -> Result<(), Error> {
+        let error = |msg: String| Error::raw(InvalidValue, format!("{}: {msg}\n", path.display()));
+        let text = fs::read_to_string(path).map_err(|it| error(it.to_string()))?;
+        let mut table = text.parse::<Table>().map_err(|it| error(it.to_string()))?;
+        if let Some(presets) = table.remove(PRESETS) {
+            let Value::Table(presets) = presets else {
+                return Err(error(format!("'{PRESETS}' isn't a table of presets")));
+            };
+            for (name, options) in presets {
+                let Value::Table(options) = options else {
+                    return Err(error(format!("the preset '{name}' isn't a table")));
+                };
+                self.presets.insert(name, options.into_iter().collect());
+            }
+        }
+        for (option, value) in table {
+            self.defaults.retain(|it| it.0 != option);
+            self.defaults.push((option, value));
+        }
+        return Ok(());
+    }
+
+    /// The arguments with the options of the config, the preset and the recipe of the source before them,
+    /// the passed options are kept as is. The source replaces the passed one to find the recipe.
+    /// The arguments of a command are left alone
+    pub fn apply(&self, args: Vec<String>, source: Option<&Path>) -> Result<Vec<String>, Error> {
+        let matches = command().try_get_matches_from(&args)?;
+        if matches.subcommand().is_some() {
+            return Ok(args);
+        }
+        let source = source.map(Path::to_path_buf).or_else(|| matches.get_one::<PathBuf>(SOURCE_ID).cloned());
+        let recipe = match source {
+            None => Vec::new(),
+            Some(source) => Recipe::load(&source)
+                .map_err(|it| Error::raw(InvalidValue, format!("{it}\n")))?
+                .map(|it| it.options.into_iter().collect())
+                .unwrap_or_default(),
+        };
+        let named = |options: &[(String, Value)]| options.iter()
+            .find(|it| it.0 == PRESET_OPTION)
+            .and_then(|it| it.1.as_str().map(String::from));
+        let preset = matches.get_one::<String>(PRESET_OPTION).cloned()
+            .or_else(|| named(&recipe))
+            .or_else(|| named(&self.defaults));
+        let preset = match preset {
+            None => &Vec::new(),
+            Some(name) => self.presets.get(&name).ok_or_else(|| {
+                let mut names = self.presets.keys().cloned().collect::<Vec<String>>();
+                names.sort();
+                Error::raw(InvalidValue, format!("no preset '{name}', the presets: {}\n", names.join(", ")))
+            })?,
+        };
+        // the preset overrides the config, the recipe overrides both
+        let mut values = Vec::<&(String, Value)>::new();
+        for it in self.defaults.iter().chain(preset).chain(&recipe) {
+            values.retain(|value| value.0 != it.0);
+            values.push(it);
+        }
+        let command = command();
+        let mut options = Vec::new();
+        for (option, value) in values {
+            let arg = command.get_arguments()
+                .find(|it| it.get_long() == Some(option.as_str()) || it.get_all_aliases().unwrap_or_default().contains(&option.as_str()))
+                .ok_or(Error::raw(InvalidValue, format!("{CONFIG_FILE}: unknown option '{option}'\n")))?;
+            if matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine) {
+                continue;
+            }
+            options.extend(to_args(option, value)?);
+        }
+        let mut result = args;
+        let rest = result.split_off(1.min(result.len()));
+        result.extend(options);
+        result.extend(rest);
+        return Ok(result);
+    }
+}
+
+/// The user config and the config of the current directory or the closest parent one, the existing ones
+pub fn config_paths() -> Vec<PathBuf> {
+    let project = env::current_dir().ok()
+        .and_then(|dir| dir.ancestors().map(|it| it.join(CONFIG_FILE)).find(|it| it.is_file()));
+    return [user_config_path(), project].into_iter().flatten().collect();
+}
+
+/// The arguments parsed with the options of the config files and of the recipe of the source, see Config::apply
+pub fn parse_with_config(args: Vec<String>, source: Option<&Path>) -> Result<Cli, Error> {
+    let applied = Config::load()?.apply(args.clone(), source)?;
+    let matches = command().try_get_matches_from(applied)?;
+    let mut cli = Cli::from_arg_matches(&matches).map_err(|it| it.format(&mut command()))?;
+    cli.args = args;
+    return Ok(cli);
+}
+
+/// The command with the hidden --no-<flag> of every flag, the passed one clears the flag of the config files
+fn command() -> Command {
+    let mut command = Cli::command();
+    let flags = command.get_arguments()
+        .filter(|it| matches!(it.get_action(), ArgAction::SetTrue))
+        .filter_map(|it| it.get_long().map(|long| (it.get_id().to_string(), format!("{NEGATION_PREFIX}{long}"))))
+        .collect::<Vec<(String, String)>>();
+    for (id, negation) in flags {
+        // the options of the config go before the passed ones, the last one of the pair wins
+        command = command
+            .mut_arg(&id, |it| it.overrides_with(&negation))
+            .arg(Arg::new(&negation).long(&negation).action(ArgAction::SetTrue).hide(true).overrides_with(&id));
+    }
+    return command;
+}
+
+fn to_args(option: &str, value: &Value) -> Result<Vec<String>, Error> {
+    return match value {
+        Value::Boolean(true) => Ok(vec![format!("--{option}")]),
+        Value::Boolean(false) => Ok(Vec::new()),
+        Value::String(value) => Ok(vec![format!("--{option}={value}")]),
+        Value::Integer(value) => Ok(vec![format!("--{option}={value}")]),
+        Value::Float(value) => Ok(vec![format!("--{option}={value}")]),
+        Value::Array(values) => values.iter()
+            .map(|it| to_args(option, it))
+            .collect::<Result<Vec<Vec<String>>, Error>>()
+            .map(|it| it.concat()),
+        _ => Err(Error::raw(InvalidValue, format!("{CONFIG_FILE}: '{option}' isn't a string, number, boolean or array\n"))),
+    };
+}
+
+// XDG on Linux, ~/.config on macOS too, and the roaming app data on Windows
+fn user_config_path() -> Option<PathBuf> {
+    let dir = env::var_os("XDG_CONFIG_HOME").map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|it| PathBuf::from(it).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    return Some(dir.join(APP_DIR).join(CONFIG_FILE)).filter(|it| it.is_file());
+}
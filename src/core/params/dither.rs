@@ -0,0 +1,60 @@
+use std::fmt::{Debug, Display, Formatter};
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Dither {
+    Pattern, FloydSteinberg, Atkinson,
+}
+
+impl Dither {
+
+    /// The neighbours a pixel passes its error to, dx, dy and the part of the error
+    pub fn kernel(&self) -> &'static [(i32, i32, f32)] {
+        match self {
+            Dither::Pattern => &[],
+            Dither::FloydSteinberg => &[(1, 0, 7.0 / 16.0), (-1, 1, 3.0 / 16.0), (0, 1, 5.0 / 16.0), (1, 1, 1.0 / 16.0)],
+            // only 3/4 of the error is passed
+            Dither::Atkinson => &[(1, 0, 0.125), (2, 0, 0.125), (-1, 1, 0.125), (0, 1, 0.125), (1, 1, 0.125), (0, 2, 0.125)],
+        }
+    }
+}
+
+impl ValueEnum for Dither {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Dither::Pattern, Dither::FloydSteinberg, Dither::Atkinson]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Dither::Pattern => PossibleValue::new("pattern").help("Dots spaced by the brightness, the lighter the sparser"),
+            Dither::FloydSteinberg => PossibleValue::new("fs").help("Floyd-Steinberg error diffusion"),
+            Dither::Atkinson => PossibleValue::new("atkinson").help("Atkinson error diffusion, more contrast in the light and dark areas"),
+        })
+    }
+}
+
+impl Display for Dither {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_possible_value().unwrap().get_name())
+    }
+}
+
+impl Debug for Dither {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::str::FromStr for Dither {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        for variant in Self::value_variants() {
+            if variant.to_possible_value().unwrap().matches(s, false) {
+                return Ok(*variant);
+            }
+        }
+        Err(format!("invalid variant: {s}"))
+    }
+}
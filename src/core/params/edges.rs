@@ -0,0 +1,53 @@
+use std::fmt::{Debug, Display, Formatter};
+
+
+const DEFAULT_SOBEL_LEVEL: u8 = 25;
+const DEFAULT_CANNY_LOW: u8 = 10;
+const DEFAULT_CANNY_HIGH: u8 = 30;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Edges {
+    /// The gradient magnitude above the percentage
+    Sobel(u8),
+    /// The hysteresis between the low and the high gradient percentages
+    Canny(u8, u8),
+    /// The border of the pixels that aren't a background
+    Outline,
+}
+
+impl Display for Edges {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Edges::Sobel(level) => write!(f, "sobel:{level}"),
+            Edges::Canny(low, high) => write!(f, "canny:{low}:{high}"),
+            Edges::Outline => write!(f, "outline"),
+        }
+    }
+}
+
+impl Debug for Edges {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::str::FromStr for Edges {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let name = parts.next().unwrap_or_default();
+        let values = parts
+            .map(|it| it.parse::<u8>().ok().filter(|&it| it <= 100).ok_or(format!("'{it}' isn't a valid percentage")))
+            .collect::<Result<Vec<u8>, String>>()?;
+        let variant = match (name, &values[..]) {
+            ("sobel", []) => Edges::Sobel(DEFAULT_SOBEL_LEVEL),
+            ("sobel", [level]) => Edges::Sobel(*level),
+            ("canny", []) => Edges::Canny(DEFAULT_CANNY_LOW, DEFAULT_CANNY_HIGH),
+            ("canny", [low, high]) if low <= high => Edges::Canny(*low, *high),
+            ("outline", []) => Edges::Outline,
+            _ => return Err(format!("invalid variant: {s}")),
+        };
+        return Ok(variant);
+    }
+}
@@ -0,0 +1,59 @@
+use std::fmt::{Debug, Display, Formatter};
+use std::path::PathBuf;
+
+
+/// The names of the firmware fonts, as the canvas of the Flipper has them
+const NAMES: [(&str, FontSource); 4] = [
+    ("primary", FontSource::Primary),
+    ("secondary", FontSource::Secondary),
+    ("keyboard", FontSource::Keyboard),
+    ("big-numbers", FontSource::BigNumbers),
+];
+
+#[derive(Clone, PartialEq, Eq)]
+pub enum FontSource {
+    /// FontPrimary, the bold helvB08
+    Primary,
+    /// FontSecondary, the haxrcorp4089
+    Secondary,
+    /// FontKeyboard, the profont11
+    Keyboard,
+    /// FontBigNumbers, the profont22 digits
+    BigNumbers,
+    /// A BDF font file
+    File(PathBuf),
+}
+
+impl Display for FontSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match NAMES.iter().find(|(_, it)| it == self) {
+            Some((name, _)) => write!(f, "{name}"),
+            None => match self {
+                FontSource::File(path) => write!(f, "{}", path.display()),
+                _ => unreachable!(),
+            },
+        }
+    }
+}
+
+impl Debug for FontSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::str::FromStr for FontSource {
+    type Err = String;
+
+    /// A firmware font name, or the path of a BDF file otherwise
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(String::from("a font name or a BDF file path is expected"));
+        }
+        let font = NAMES.iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(s))
+            .map(|(_, it)| it.clone())
+            .unwrap_or_else(|| FontSource::File(PathBuf::from(s)));
+        return Ok(font);
+    }
+}
@@ -0,0 +1,25 @@
+use image::RgbaImage;
+use crate::core::params::key_color::KeyColor;
+use crate::core::params::params::Params;
+use crate::core::params::threshold::Threshold;
+use crate::core::temporal::PreviousFrame;
+
+
+/// Parameters resolved for the particular picture or GIF frame
+pub struct FrameParams {
+    pub threshold: Threshold,
+    pub key: Option<KeyColor>,
+    /// The previous GIF frame for the temporal dithering
+    pub previous: Option<PreviousFrame>,
+}
+
+impl FrameParams {
+
+    pub fn from(params: &Params, image: &RgbaImage) -> FrameParams {
+        FrameParams {
+            threshold: params.threshold.clone(),
+            key: params.key.map(|it| it.resolve(image)),
+            previous: None,
+        }
+    }
+}
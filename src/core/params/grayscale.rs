@@ -0,0 +1,92 @@
+use std::fmt::{Debug, Display, Formatter};
+
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum Grayscale {
+    /// Rec.709 luma
+    Luminance,
+    Average,
+    /// The mean of the brightest and the darkest channels
+    Lightness,
+    Red,
+    Green,
+    Blue,
+    /// Opaque is dark, transparent is light
+    Alpha,
+    /// Normalized weights of red, green and blue channels
+    Weights(f32, f32, f32),
+}
+
+impl Grayscale {
+
+    pub fn weights(red: f32, green: f32, blue: f32) -> Option<Grayscale> {
+        let sum = red + green + blue;
+        if red < 0.0 || green < 0.0 || blue < 0.0 || sum <= 0.0 {
+            return None;
+        }
+        return Some(Grayscale::Weights(red / sum, green / sum, blue / sum));
+    }
+
+    pub fn luma(&self, pixel: [u8; 4]) -> u8 {
+        let [r, g, b, a] = pixel.map(|it| it as f32);
+        let value = match *self {
+            Grayscale::Luminance => 0.2126 * r + 0.7152 * g + 0.0722 * b,
+            Grayscale::Average => (r + g + b) / 3.0,
+            Grayscale::Lightness => (r.max(g).max(b) + r.min(g).min(b)) / 2.0,
+            Grayscale::Red => r,
+            Grayscale::Green => g,
+            Grayscale::Blue => b,
+            Grayscale::Alpha => 255.0 - a,
+            Grayscale::Weights(wr, wg, wb) => wr * r + wg * g + wb * b,
+        };
+        return value.round().clamp(0.0, 255.0) as u8;
+    }
+}
+
+impl Display for Grayscale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Grayscale::Luminance => write!(f, "luminance"),
+            Grayscale::Average => write!(f, "average"),
+            Grayscale::Lightness => write!(f, "lightness"),
+            Grayscale::Red => write!(f, "red"),
+            Grayscale::Green => write!(f, "green"),
+            Grayscale::Blue => write!(f, "blue"),
+            Grayscale::Alpha => write!(f, "alpha"),
+            Grayscale::Weights(r, g, b) => write!(f, "{r}:{g}:{b}"),
+        }
+    }
+}
+
+impl Debug for Grayscale {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::str::FromStr for Grayscale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let variant = match s {
+            "luminance" => Grayscale::Luminance,
+            "average" => Grayscale::Average,
+            "lightness" => Grayscale::Lightness,
+            "red" | "r" => Grayscale::Red,
+            "green" | "g" => Grayscale::Green,
+            "blue" | "b" => Grayscale::Blue,
+            "alpha" | "a" => Grayscale::Alpha,
+            _ => {
+                let weights = s.split(':')
+                    .map(|it| it.parse::<f32>())
+                    .collect::<Result<Vec<f32>, _>>()
+                    .map_err(|_| format!("invalid variant: {s}"))?;
+                match weights[..] {
+                    [r, g, b] => Grayscale::weights(r, g, b).ok_or(format!("invalid weights: {s}"))?,
+                    _ => return Err(format!("invalid variant: {s}")),
+                }
+            },
+        };
+        return Ok(variant);
+    }
+}
@@ -0,0 +1,36 @@
+use std::fmt::{Debug, Display, Formatter};
+use image::RgbaImage;
+use crate::core::color::{corners_color, Color};
+
+
+pub const DEFAULT_KEY_TOLERANCE: u8 = 50;
+
+#[derive(Copy, Clone)]
+pub struct KeyColor {
+    /// None to sample the corners of the picture
+    pub color: Option<Color>,
+    pub tolerance: u8,
+}
+
+impl KeyColor {
+
+    pub fn resolve(&self, image: &RgbaImage) -> KeyColor {
+        let color = self.color.unwrap_or_else(|| corners_color(image, self.tolerance));
+        KeyColor { color: Some(color), tolerance: self.tolerance }
+    }
+}
+
+impl Display for KeyColor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.color {
+            None => write!(f, "auto:{}", self.tolerance),
+            Some(Color { r, g, b }) => write!(f, "{r:02x}{g:02x}{b:02x}:{}", self.tolerance),
+        }
+    }
+}
+
+impl Debug for KeyColor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
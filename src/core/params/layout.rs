@@ -0,0 +1,48 @@
+use std::fmt::{Debug, Display, Formatter};
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Layout {
+    HorizontalLsb, HorizontalMsb, PagesLsb, PagesMsb
+}
+
+impl ValueEnum for Layout {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Layout::HorizontalLsb, Layout::HorizontalMsb, Layout::PagesLsb, Layout::PagesMsb]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Layout::HorizontalLsb => PossibleValue::new("horizontal-lsb").help("Rows of bytes, the leftmost pixel is the lowest bit, as Flipper and XBM have"),
+            Layout::HorizontalMsb => PossibleValue::new("horizontal-msb").help("Rows of bytes, the leftmost pixel is the highest bit"),
+            Layout::PagesLsb => PossibleValue::new("pages-lsb").help("8-pixel vertical pages, the top pixel is the lowest bit, as SSD1306 and ST7565 have"),
+            Layout::PagesMsb => PossibleValue::new("pages-msb").help("8-pixel vertical pages, the top pixel is the highest bit"),
+        })
+    }
+}
+
+impl Display for Layout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_possible_value().unwrap().get_name())
+    }
+}
+
+impl Debug for Layout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::str::FromStr for Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        for variant in Self::value_variants() {
+            if variant.to_possible_value().unwrap().matches(s, false) {
+                return Ok(*variant);
+            }
+        }
+        Err(format!("invalid variant: {s}"))
+    }
+}
@@ -0,0 +1,85 @@
+use std::fmt::{Debug, Display, Formatter};
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use crate::ext::path_ext::{EXT_BIN, EXT_BM, EXT_BMX, EXT_PBM, EXT_PNG, EXT_XBM};
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OutputFormat {
+    Bm, Xbm, Pbm, PbmAscii, Raw, Png, Icon, Bmx
+}
+
+impl OutputFormat {
+
+    pub fn ext(&self) -> &'static str {
+        match self {
+            OutputFormat::Bm => EXT_BM,
+            OutputFormat::Xbm => EXT_XBM,
+            OutputFormat::Pbm | OutputFormat::PbmAscii => EXT_PBM,
+            OutputFormat::Raw => EXT_BIN,
+            OutputFormat::Png | OutputFormat::Icon => EXT_PNG,
+            OutputFormat::Bmx => EXT_BMX,
+        }
+    }
+
+    /// The asset pack icons keep their animation frames in .bm
+    pub fn frame_ext(&self) -> &'static str {
+        match self {
+            OutputFormat::Bmx => EXT_BM,
+            _ => self.ext(),
+        }
+    }
+
+    /// The animation frames are written one per played frame, with the frame rate aside
+    pub fn in_play_order(&self) -> bool {
+        matches!(self, OutputFormat::Icon | OutputFormat::Bmx)
+    }
+
+    /// The firmware sources, they are compiled by the firmware's own asset compiler
+    pub fn is_asset_source(&self) -> bool {
+        matches!(self, OutputFormat::Png | OutputFormat::Icon)
+    }
+}
+
+impl ValueEnum for OutputFormat {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[OutputFormat::Bm, OutputFormat::Xbm, OutputFormat::Pbm, OutputFormat::PbmAscii, OutputFormat::Raw, OutputFormat::Png, OutputFormat::Icon, OutputFormat::Bmx]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+        Some(match self {
+            OutputFormat::Bm => PossibleValue::new("bm").help("Flipper bitmap with the Flipper Animation files"),
+            OutputFormat::Xbm => PossibleValue::new("xbm").help("X BitMap C source, as u8g2 draws it"),
+            OutputFormat::Pbm => PossibleValue::new("pbm").help("Binary Netpbm bitmap"),
+            OutputFormat::PbmAscii => PossibleValue::new("pbm-ascii").help("Plain text Netpbm bitmap"),
+            OutputFormat::Raw => PossibleValue::new("raw").help("Headerless bytes in the layout, animation frames go one by one into a single file"),
+            OutputFormat::Png => PossibleValue::new("png").help("1-bit PNG frames with the Flipper Animation files, as assets/dolphin keeps them"),
+            OutputFormat::Icon => PossibleValue::new("icon").help("1-bit PNG frame_XX.png in play order with the frame_rate file, as assets/icons keeps them"),
+            OutputFormat::Bmx => PossibleValue::new("bmx").help("Asset pack icon, the size ahead of the compressed bm, animation frames go with the meta"),
+        })
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_possible_value().unwrap().get_name())
+    }
+}
+
+impl Debug for OutputFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        for variant in Self::value_variants() {
+            if variant.to_possible_value().unwrap().matches(s, false) {
+                return Ok(*variant);
+            }
+        }
+        Err(format!("invalid variant: {s}"))
+    }
+}
@@ -1,21 +1,46 @@
 use std::fmt::Display;
-use clap::{CommandFactory, Parser};
+use std::fs;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use image::codecs::png::PngDecoder;
+use clap::CommandFactory;
 use clap::Error;
 use clap::error::ErrorKind;
 use ErrorKind::InvalidValue;
 use crate::core::params::alignment::Alignment;
+use crate::core::asset_pack::ICON_META_FILE;
 use crate::core::params::args::Cli;
+use crate::core::params::config::{config_paths, parse_with_config};
+use crate::core::params::recipe::Recipe;
 use crate::core::params::background::Background;
+use crate::core::params::dither::Dither;
 use crate::core::params::frame_cut::FrameCut;
+use crate::core::params::grayscale::Grayscale;
+use crate::core::params::alpha::Alpha;
+use crate::core::params::edges::Edges;
+use crate::core::params::sharpen::Sharpen;
+use crate::core::params::key_color::KeyColor;
+use crate::core::params::font_source::FontSource;
+use crate::core::font::Font;
+use crate::core::text::Text;
+use crate::core::params::term_preview::TermPreview;
+use crate::core::params::preview_theme::PreviewTheme;
+use crate::core::params::c_array::CArray;
+use crate::core::params::output_format::OutputFormat;
+use crate::core::params::layout::Layout;
 use crate::core::params::scale_type::ScaleType;
+use crate::core::params::sweep::Sweep;
 use crate::core::params::threshold::Threshold;
-use crate::ext::path_ext::{PathExt, EXT_PNG, EXT_GIF, EXT_BM, EXT_PICTURE};
+use crate::ext::path_ext::{PathExt, EXT_PNG, EXT_GIF, EXT_PICTURE, EXT_H, EXT_C};
 
 
 const TARGET_WIDTH: u8 = 128;
 
 pub enum FileType {
-    Picture, Gif
+    Picture, Gif, Apng,
+    /// The firmware sources of an animation, frame_N.png with meta.txt or frame_XX.png with frame_rate
+    Folder,
 }
 
 pub struct Params {
@@ -24,16 +49,51 @@ pub struct Params {
     pub height: u8,
     pub preview: bool,
     pub only_preview: bool,
+    pub term_preview: Option<TermPreview>,
     pub preview_scale: u32,
+    pub preview_theme: PreviewTheme,
+    pub pixel_grid: bool,
+    pub screen: bool,
+    pub compare: bool,
+    pub contact_sheet: Vec<Threshold>,
+    pub sweep: Vec<Sweep>,
+    pub sweep_files: bool,
     pub inverse: bool,
     pub background: Background,
     pub threshold: Threshold,
+    pub dither: Dither,
+    pub frame_threshold: bool,
+    pub gray: Grayscale,
+    pub alpha: Alpha,
+    pub sharpen: Option<Sharpen>,
+    pub edges: Option<Edges>,
+    pub stroke: u8,
+    pub edges_fill: bool,
+    pub key: Option<KeyColor>,
+    pub key_visible: bool,
+    pub frame_key: bool,
+    pub temporal: Option<u8>,
+    pub scene_path: Option<String>,
+    pub scene_position: (i64, i64),
+    pub scene_above: bool,
+    pub sprite_key: Option<KeyColor>,
+    /// The text to draw over the output frames with its font loaded
+    pub text: Option<Text>,
+    pub format: OutputFormat,
+    pub layout: Layout,
+    pub c_array: Option<CArray>,
+    pub c_icon: bool,
     pub cut: FrameCut,
     pub scale_type: ScaleType,
     pub alignment: Alignment,
     pub speed: f32,
     pub with_manifest: bool,
     pub replace_manifest: bool,
+    /// The directory to build an asset pack in from the source folder
+    pub asset_pack: Option<String>,
+    /// The sidecar recipe of the source, the default one without it
+    pub recipe: Recipe,
+    pub watch: bool,
 
     pub path_src: String,
     pub path_name: String,
@@ -41,12 +101,25 @@ pub struct Params {
     pub preview_path_name: String,
     pub preview_picture_path: String,
     pub preview_gif_path: String,
-    pub picture_path_bm: String,
+    pub compare_picture_path: String,
+    pub compare_gif_path: String,
+    pub contact_sheet_path: String,
+    pub sweep_picture_path: String,
+    pub sweep_gif_path: String,
+    /// The output file of a picture, or of all the frames in the raw format
+    pub picture_path_dst: String,
     pub dolphin_path: String,
     pub dolphin_anim_name: String,
     pub dolphin_anim_path: String,
     pub meta_path: String,
+    /// The frame rate of the assets/icons animation, or the meta of the asset pack one
+    pub frame_rate_path: String,
     pub manifest_path: String,
+    pub c_name: String,
+    pub c_header_path: String,
+    pub c_source_path: String,
+
+    cli: Cli,
 }
 
 impl Params {
@@ -55,72 +128,321 @@ impl Params {
         Cli::command().print_help().unwrap();
     }
 
-    pub fn try_parse() -> Result<Params, Error> {
-        return Params::from(Cli::parse());
-    }
-
-    pub fn try_parse_from(string: String) -> Result<Params, Error> {
-        let mut args = shell_words::split(string.as_str()).expect("wrong arguments format");
-        args.insert(0, "stub".to_string());
-        let cli = Cli::try_parse_from(args)?;
-        return Params::from(cli);
-    }
-
     pub fn from(cli: Cli) -> Result<Params, Error> {
-        cli.source_path.extension().ok_or(Error::raw(InvalidValue, "invalid input file"))?;
-        cli.source_path.file_name().ok_or(Error::raw(InvalidValue, "invalid input file path"))?;
-        let input_ext = cli.source_path.get_ext().to_lowercase();
+        if cli.source().is_dir() {
+            return Params::from_folder(cli);
+        }
+        if cli.asset_pack.is_some() {
+            return Err(Error::raw(InvalidValue, "an asset pack is built from a folder\n"));
+        }
+        cli.source().extension().ok_or(Error::raw(InvalidValue, "invalid input file\n"))?;
+        cli.source().file_name().ok_or(Error::raw(InvalidValue, "invalid input file path\n"))?;
+        let input_ext = cli.source().get_ext().to_lowercase();
         let file_type = match () {
+            _ if input_ext == EXT_PNG && is_apng(cli.source()) => FileType::Apng,
             _ if EXT_PICTURE.contains(&&*input_ext) => FileType::Picture,
             _ if input_ext == EXT_GIF => FileType::Gif,
-            _ => return Err(Error::raw(InvalidValue, "invalid input file format")),
+            _ => return Err(Error::raw(InvalidValue, "invalid input file format\n")),
+        };
+        let path_name = cli.source().get_path_name();
+        let name = cli.source().get_name_no_ext();
+        let dolphin_anim_name = format!("{name}_{TARGET_WIDTH}x{}", cli.height);
+        // the source picture can be a png too
+        let picture_path_dst = match cli.format.is_asset_source() {
+            true => format!("{}{dolphin_anim_name}.{EXT_PNG}", cli.source().get_parent()),
+            false => format!("{path_name}.{}", cli.format.ext()),
+        };
+        return Params::with_paths(cli, file_type, input_ext, path_name, name, dolphin_anim_name, picture_path_dst);
+    }
+
+    fn from_folder(cli: Cli) -> Result<Params, Error> {
+        let name = cli.source().file_name()
+            .and_then(|it| it.to_str())
+            .ok_or(Error::raw(InvalidValue, "invalid input folder path\n"))?
+            .to_string();
+        let packing = cli.asset_pack.is_some();
+        if !packing && (matches!(cli.format, OutputFormat::Raw | OutputFormat::Bmx) || cli.format.is_asset_source()) {
+            return Err(Error::raw(InvalidValue, "a folder of frames converts only into the bm, xbm or pbm frames\n"));
+        }
+        if !packing && (cli.compare || !cli.contact_sheet.is_empty() || !cli.sweep.is_empty()) {
+            return Err(Error::raw(InvalidValue, "a folder of frames is converted pixel to pixel, there is nothing to compare\n"));
+        }
+        let path_name = cli.source().to_string().trim_end_matches('/').to_string();
+        let picture_path_dst = format!("{path_name}.{}", cli.format.ext());
+        // the frames are written next to the source folder without the dolphin directory
+        let anim_name = match cli.dolphin_path {
+            Some(_) => name.clone(),
+            None => format!("{name}_bm"),
         };
-        let path_name = cli.source_path.get_path_name();
-        let preview_path_name = format!("{}_preview", cli.source_path.get_path_name());
+        let params = Params::with_paths(cli, FileType::Folder, String::new(), path_name, name, anim_name, picture_path_dst)?;
+        if !packing && is_same_dir(Path::new(&params.dolphin_anim_path), Path::new(&params.path_src)) {
+            return Err(Error::raw(InvalidValue, "the frames would be written into the source folder, pass another dolphin directory\n"));
+        }
+        return Ok(params);
+    }
+
+    fn with_paths(
+        cli: Cli,
+        file_type: FileType,
+        input_ext: String,
+        path_name: String,
+        name: String,
+        dolphin_anim_name: String,
+        picture_path_dst: String,
+    ) -> Result<Params, Error> {
+        if cli.layout != Layout::HorizontalLsb && (cli.c_icon || cli.c_array == Some(CArray::Compressed)) {
+            return Err(Error::raw(InvalidValue, "the compressed C arrays and the Icon struct need the horizontal-lsb layout\n"));
+        }
+        let preview_path_name = format!("{path_name}_preview");
         let preview_picture_path = format!("{preview_path_name}.{EXT_PNG}");
         let preview_gif_path = format!("{preview_path_name}.{EXT_GIF}");
-        let picture_path_bm = format!("{path_name}.{EXT_BM}");
+        let compare_picture_path = format!("{path_name}_compare.{EXT_PNG}");
+        let compare_gif_path = format!("{path_name}_compare.{EXT_GIF}");
+        let contact_sheet_path = format!("{path_name}_contact.{EXT_PNG}");
+        let sweep_picture_path = format!("{path_name}_sweep.{EXT_PNG}");
+        let sweep_gif_path = format!("{path_name}_sweep.{EXT_GIF}");
         let dolphin_path = cli.dolphin_path.clone()
             .map(|it| it.as_dir())
-            .unwrap_or_else(|| cli.source_path.get_parent());
-        let dolphin_anim_name = format!("{}_{TARGET_WIDTH}x{}", cli.source_path.get_name_no_ext(), cli.height);
+            .unwrap_or_else(|| cli.source().get_parent());
         let dolphin_anim_path = format!("{dolphin_path}{dolphin_anim_name}/");
         let meta_path = format!("{dolphin_anim_path}meta.txt");
+        let frame_rate_path = frame_rate_path(cli.format, &dolphin_anim_path);
         let manifest_path = format!("{dolphin_path}manifest.txt");
+        let c_name = name;
+        let args = cli.clone();
+        let path_src = cli.source().to_string();
+        let recipe = Recipe::load(cli.source())
+            .map_err(|it| Error::raw(InvalidValue, format!("{it}\n")))?
+            .unwrap_or_default();
+        let text = match cli.text.is_empty() {
+            true => None,
+            false => Some(Text {
+                font: Font::load(&cli.font).map_err(|it| Error::raw(InvalidValue, format!("{it}\n")))?,
+                lines: cli.text,
+                x: cli.text_position.0 as i32,
+                y: cli.text_position.1 as i32,
+                align: cli.text_align,
+                boxed: cli.text_box,
+                marquee: cli.marquee,
+            }),
+        };
+        let c_header_path = format!("{path_name}.{EXT_H}");
+        let c_source_path = format!("{path_name}.{EXT_C}");
         let params = Params {
             file_type,
             width: TARGET_WIDTH,
             height: cli.height,
-            preview: cli.preview || cli.only_preview,
+            preview: cli.preview || (cli.only_preview && cli.term_preview.is_none()),
             only_preview: cli.only_preview,
+            term_preview: cli.term_preview.map(|it| it.resolve()),
             preview_scale: cli.preview_scale as u32,
+            preview_theme: cli.preview_theme,
+            pixel_grid: cli.pixel_grid,
+            screen: cli.screen,
+            compare: cli.compare,
+            contact_sheet: cli.contact_sheet,
+            sweep: cli.sweep,
+            sweep_files: cli.sweep_files,
             inverse: cli.inverse,
             background: cli.background,
             threshold: cli.threshold,
+            dither: cli.dither,
+            frame_threshold: cli.frame_threshold,
+            gray: cli.gray,
+            alpha: cli.alpha,
+            sharpen: cli.sharpen,
+            edges: cli.edges,
+            stroke: cli.stroke,
+            edges_fill: cli.edges_fill,
+            key: cli.key_color,
+            key_visible: cli.key_visible,
+            frame_key: cli.frame_key,
+            temporal: cli.temporal,
+            scene_path: cli.scene.as_ref().map(|it| it.to_string()),
+            scene_position: cli.scene_position,
+            scene_above: cli.scene_above,
+            sprite_key: cli.sprite_key,
+            text,
+            format: cli.format,
+            layout: cli.layout,
+            c_array: cli.c_array,
+            c_icon: cli.c_icon,
             cut: cli.cut,
             scale_type: cli.scale_type,
             alignment: cli.alignment,
             speed: cli.speed,
             with_manifest: cli.dolphin_path.is_some(),
             replace_manifest: cli.replace_manifest,
+            asset_pack: cli.asset_pack.as_ref().map(|it| it.as_dir()),
+            recipe,
+            watch: cli.watch,
 
-            path_src: cli.source_path.to_string(),
+            path_src,
             path_name,
             input_ext,
             preview_path_name,
             preview_picture_path,
             preview_gif_path,
-            picture_path_bm,
+            compare_picture_path,
+            compare_gif_path,
+            contact_sheet_path,
+            sweep_picture_path,
+            sweep_gif_path,
+            picture_path_dst,
             dolphin_path,
             dolphin_anim_name,
             dolphin_anim_path,
             meta_path,
+            frame_rate_path,
             manifest_path,
+            c_name,
+            c_header_path,
+            c_source_path,
+            cli: args,
         };
         return Ok(params);
     }
 
-    pub fn path_bm<I>(&self, index: I) -> String where I: Display {
-        format!("{}frame_{}.{EXT_BM}", self.dolphin_anim_path, index)
+    pub fn path_frame<I>(&self, index: I) -> String where I: Display {
+        format!("{}frame_{}.{}", self.dolphin_anim_path, index, self.format.frame_ext())
+    }
+
+    /// The assets/icons and asset pack frames are played in the order of their names
+    pub fn path_icon_frame(&self, index: usize) -> String {
+        format!("{}frame_{index:02}.{}", self.dolphin_anim_path, self.format.frame_ext())
+    }
+
+    /// The same options for another source, written as the name into the directory
+    pub fn for_asset(
+        &self,
+        source: &Path,
+        format: OutputFormat,
+        size: (u8, u8),
+        dir: &str,
+        name: &str,
+    ) -> Result<Params, Error> {
+        let mut cli = parse_with_config(self.cli.args.clone(), Some(source))?;
+        cli.source_path = Some(source.to_path_buf());
+        cli.dolphin_path = Some(PathBuf::from(dir));
+        cli.format = format;
+        cli.height = size.1;
+        cli.preview = false;
+        cli.only_preview = false;
+        cli.term_preview = None;
+        cli.compare = false;
+        cli.contact_sheet = Vec::new();
+        cli.sweep = Vec::new();
+        cli.c_array = None;
+        cli.asset_pack = None;
+        let mut params = Params::from(cli)?;
+        params.width = size.0;
+        params.picture_path_dst = format!("{}{name}.{}", params.dolphin_path, format.ext());
+        params.dolphin_anim_name = name.to_string();
+        params.dolphin_anim_path = format!("{}{name}/", params.dolphin_path);
+        params.meta_path = format!("{}meta.txt", params.dolphin_anim_path);
+        params.frame_rate_path = frame_rate_path(format, &params.dolphin_anim_path);
+        return Ok(params);
+    }
+
+    /// The passed arguments to parse it again
+    pub fn args(&self) -> &[String] {
+        &self.cli.args
+    }
+
+    /// The files the conversion is made from: the source, its recipe, the scene and the font file
+    pub fn input_paths(&self) -> Vec<PathBuf> {
+        let mut paths = vec![PathBuf::from(&self.path_src), Recipe::path(Path::new(&self.path_src))];
+        paths.extend(self.scene_path.iter().map(PathBuf::from));
+        if let FontSource::File(path) = &self.cli.font {
+            paths.push(path.clone());
+        }
+        return paths;
+    }
+
+    /// The input files and the config files, the directories are of the files to watch
+    pub fn watched_paths(&self) -> Vec<PathBuf> {
+        let mut paths = self.input_paths();
+        paths.extend(config_paths());
+        return paths;
+    }
+
+    /// The options the output is converted with, to tell whether it's up to date,
+    /// without the passed arguments, the paths and the options of the previews and of the watching
+    pub fn options(&self) -> String {
+        let mut cli = self.cli.clone();
+        cli.args = Vec::new();
+        cli.source_path = None;
+        cli.dolphin_path = None;
+        cli.asset_pack = None;
+        cli.preset = None;
+        cli.watch = false;
+        cli.replace_manifest = false;
+        cli.preview = false;
+        cli.only_preview = false;
+        cli.term_preview = None;
+        cli.preview_scale = 0;
+        cli.preview_theme = PreviewTheme::Plain;
+        cli.pixel_grid = false;
+        cli.screen = false;
+        cli.compare = false;
+        cli.contact_sheet = Vec::new();
+        cli.sweep = Vec::new();
+        cli.sweep_files = false;
+        return format!("{cli:?}");
+    }
+
+    /// The same options with every combination of the sweep values, labeled with the values,
+    /// the outputs are named with the values and aren't added to the manifest
+    pub fn sweep_variants(&self) -> Result<Vec<(String, Params)>, Error> {
+        let mut variants = Vec::new();
+        for combination in Sweep::combinations(&self.sweep) {
+            let mut args = self.cli.args.clone();
+            args.extend(combination.iter().map(|(option, value)| format!("--{option}={value}")));
+            let mut cli = parse_with_config(args, None)?;
+            cli.sweep = Vec::new();
+            cli.preview = false;
+            cli.only_preview = !self.sweep_files;
+            cli.term_preview = None;
+            cli.compare = false;
+            cli.contact_sheet = Vec::new();
+            cli.c_array = None;
+            let label = combination.iter()
+                .map(|(option, value)| format!("{option}={value}"))
+                .collect::<Vec<String>>();
+            let suffix = label.join("_").replace(|it: char| !it.is_ascii_alphanumeric() && it != '_', "-");
+            let mut params = Params::from(cli)?;
+            params.picture_path_dst = match params.picture_path_dst.rsplit_once('.') {
+                Some((path, ext)) => format!("{path}_{suffix}.{ext}"),
+                None => format!("{}_{suffix}", params.picture_path_dst),
+            };
+            params.dolphin_anim_name = format!("{}_{suffix}", params.dolphin_anim_name);
+            params.dolphin_anim_path = format!("{}{}/", params.dolphin_path, params.dolphin_anim_name);
+            params.meta_path = format!("{}meta.txt", params.dolphin_anim_path);
+            params.frame_rate_path = frame_rate_path(params.format, &params.dolphin_anim_path);
+            params.with_manifest = false;
+            variants.push((label.join("\n"), params));
+        }
+        return Ok(variants);
     }
 }
+
+fn frame_rate_path(format: OutputFormat, anim_path: &str) -> String {
+    match format {
+        OutputFormat::Bmx => format!("{anim_path}{ICON_META_FILE}"),
+        _ => format!("{anim_path}frame_rate"),
+    }
+}
+
+fn is_same_dir(first: &Path, second: &Path) -> bool {
+    match (fs::canonicalize(first), fs::canonicalize(second)) {
+        (Ok(first), Ok(second)) => first == second,
+        _ => false,
+    }
+}
+
+fn is_apng(path: &PathBuf) -> bool {
+    File::open(path).ok()
+        .and_then(|file| PngDecoder::new(BufReader::new(file)).ok())
+        .and_then(|decoder| decoder.is_apng().ok())
+        .unwrap_or(false)
+}
@@ -0,0 +1,72 @@
+use std::fmt::{Debug, Display, Formatter};
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use image::Rgb;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PreviewTheme {
+    Plain, Lcd
+}
+
+impl PreviewTheme {
+
+    pub fn background(&self) -> Rgb<u8> {
+        match self {
+            PreviewTheme::Plain => Rgb([255, 255, 255]),
+            PreviewTheme::Lcd => Rgb([254, 138, 44]),
+        }
+    }
+
+    pub fn pixel(&self) -> Rgb<u8> {
+        match self {
+            PreviewTheme::Plain => Rgb([0, 0, 0]),
+            PreviewTheme::Lcd => Rgb([26, 14, 4]),
+        }
+    }
+
+    /// The background between the pixels, a bit darker than the unlit pixels
+    pub fn gap(&self) -> Rgb<u8> {
+        match self {
+            PreviewTheme::Plain => Rgb([224, 224, 224]),
+            PreviewTheme::Lcd => Rgb([232, 122, 36]),
+        }
+    }
+}
+
+impl ValueEnum for PreviewTheme {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[PreviewTheme::Plain, PreviewTheme::Lcd]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+        Some(match self {
+            PreviewTheme::Plain => PossibleValue::new("plain").help("Black pixels on white"),
+            PreviewTheme::Lcd => PossibleValue::new("lcd").help("Dark pixels on the orange backlit LCD of the Flipper"),
+        })
+    }
+}
+
+impl Display for PreviewTheme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_possible_value().unwrap().get_name())
+    }
+}
+
+impl Debug for PreviewTheme {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::str::FromStr for PreviewTheme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        for variant in Self::value_variants() {
+            if variant.to_possible_value().unwrap().matches(s, false) {
+                return Ok(*variant);
+            }
+        }
+        Err(format!("invalid variant: {s}"))
+    }
+}
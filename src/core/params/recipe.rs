@@ -0,0 +1,68 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use toml::Table;
+use crate::core::dolphin::Bubble;
+
+
+const RECIPE_EXT: &str = "img2fbm.toml";
+
+/// The values of the manifest.txt entry
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ManifestValues {
+    pub min_butthurt: u32,
+    pub max_butthurt: u32,
+    pub min_level: u32,
+    pub max_level: u32,
+    pub weight: u32,
+}
+
+impl Default for ManifestValues {
+    fn default() -> Self {
+        ManifestValues { min_butthurt: 0, max_butthurt: 13, min_level: 1, max_level: 3, weight: 8 }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnimationValues {
+    /// The source frames to convert in this order, a frame can be picked more than once, the cut is applied after
+    pub frames: Option<Vec<usize>>,
+    /// The frames of the order played while idle, the rest are the active ones
+    pub passive_frames: Option<usize>,
+    pub active_cycles: Option<u32>,
+    pub active_cooldown: u32,
+}
+
+/// The sidecar foo.img2fbm.toml of the foo.gif source, how to convert it into the animation
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Recipe {
+    pub manifest: ManifestValues,
+    pub animation: AnimationValues,
+    pub bubbles: Vec<Bubble>,
+    /// The options by their long names, as img2fbm.toml has them
+    #[serde(flatten)]
+    pub options: Table,
+}
+
+impl Recipe {
+
+    pub fn path(source: &Path) -> PathBuf {
+        let mut path = source.with_extension("").into_os_string();
+        path.push(format!(".{RECIPE_EXT}"));
+        return PathBuf::from(path);
+    }
+
+    /// None if there is no recipe next to the source
+    pub fn load(source: &Path) -> Result<Option<Recipe>, String> {
+        let path = Recipe::path(source);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let error = |msg: String| format!("{}: {msg}", path.display());
+        let text = fs::read_to_string(&path).map_err(|it| error(it.to_string()))?;
+        return toml::from_str::<Recipe>(&text).map(Some).map_err(|it| error(it.to_string()));
+    }
+}
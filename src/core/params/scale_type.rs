@@ -22,7 +22,7 @@ impl ValueEnum for ScaleType {
 
 impl Display for ScaleType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
+        write!(f, "{}", self.to_possible_value().unwrap().get_name())
     }
 }
 
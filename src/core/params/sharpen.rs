@@ -0,0 +1,49 @@
+use std::fmt::{Debug, Display, Formatter};
+
+
+const DEFAULT_RADIUS: u32 = 2;
+const DEFAULT_AMOUNT: u32 = 100;
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Sharpen {
+    /// The difference with the gaussian blur of the radius is added by the amount percentage
+    Unsharp(u32, u32),
+    /// The laplacian of the 4 closest neighbours is added by the amount percentage
+    Laplacian(u32),
+}
+
+impl Display for Sharpen {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Sharpen::Unsharp(radius, amount) => write!(f, "unsharp:{radius}:{amount}"),
+            Sharpen::Laplacian(amount) => write!(f, "laplacian:{amount}"),
+        }
+    }
+}
+
+impl Debug for Sharpen {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::str::FromStr for Sharpen {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(':');
+        let name = parts.next().unwrap_or_default();
+        let values = parts
+            .map(|it| it.parse::<u32>().map_err(|_| format!("'{it}' isn't a valid number")))
+            .collect::<Result<Vec<u32>, String>>()?;
+        let variant = match (name, &values[..]) {
+            ("unsharp", []) => Sharpen::Unsharp(DEFAULT_RADIUS, DEFAULT_AMOUNT),
+            ("unsharp", [radius]) => Sharpen::Unsharp(*radius, DEFAULT_AMOUNT),
+            ("unsharp", [radius, amount]) => Sharpen::Unsharp(*radius, *amount),
+            ("laplacian", []) => Sharpen::Laplacian(DEFAULT_AMOUNT),
+            ("laplacian", [amount]) => Sharpen::Laplacian(*amount),
+            _ => return Err(format!("invalid variant: {s}")),
+        };
+        return Ok(variant);
+    }
+}
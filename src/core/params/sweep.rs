@@ -0,0 +1,42 @@
+use std::fmt::{Debug, Display, Formatter};
+
+
+/// The values of an option to convert with one by one
+#[derive(Clone)]
+pub struct Sweep {
+    /// The long option name without the dashes
+    pub option: String,
+    pub values: Vec<String>,
+}
+
+impl Sweep {
+
+    /// Every combination of the values, as the option and value pairs
+    pub fn combinations(sweeps: &[Sweep]) -> Vec<Vec<(String, String)>> {
+        let mut combinations = vec![Vec::new()];
+        for sweep in sweeps {
+            combinations = combinations.into_iter()
+                .flat_map(|combination: Vec<(String, String)>| {
+                    sweep.values.iter().map(move |value| {
+                        let mut combination = combination.clone();
+                        combination.push((sweep.option.clone(), value.clone()));
+                        combination
+                    })
+                })
+                .collect();
+        }
+        return combinations;
+    }
+}
+
+impl Display for Sweep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}={}", self.option, self.values.join(","))
+    }
+}
+
+impl Debug for Sweep {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
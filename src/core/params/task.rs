@@ -0,0 +1,40 @@
+use std::path::PathBuf;
+use clap::Error;
+use crate::core::params::args::{Cli, Command};
+use crate::core::params::config::parse_with_config;
+use crate::core::params::params::Params;
+
+
+/// What the arguments ask to do
+pub enum Task {
+    Convert(Box<Params>),
+    Check(PathBuf),
+    /// The path and whether to print JSON
+    Info(PathBuf, bool),
+}
+
+impl Task {
+
+    pub fn try_parse() -> Result<Task, Error> {
+        return Task::try_parse_args(std::env::args().collect());
+    }
+
+    pub fn try_parse_from(string: String) -> Result<Task, Error> {
+        let mut args = shell_words::split(string.as_str()).expect("wrong arguments format");
+        args.insert(0, "stub".to_string());
+        return Task::try_parse_args(args);
+    }
+
+    /// The options of the config files, the preset and the recipe go before the passed ones
+    pub fn try_parse_args(args: Vec<String>) -> Result<Task, Error> {
+        return Task::from(parse_with_config(args, None)?);
+    }
+
+    pub fn from(cli: Cli) -> Result<Task, Error> {
+        return match cli.command.clone() {
+            Some(Command::Check { dolphin_path }) => Ok(Task::Check(dolphin_path)),
+            Some(Command::Info { path, json }) => Ok(Task::Info(path, json)),
+            None => Params::from(cli).map(|it| Task::Convert(Box::new(it))),
+        };
+    }
+}
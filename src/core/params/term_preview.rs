@@ -0,0 +1,67 @@
+use std::env;
+use std::fmt::{Debug, Display, Formatter};
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TermPreview {
+    Auto, Blocks, Braille, Sixel, Kitty
+}
+
+impl TermPreview {
+
+    /// The graphics protocol the terminal declares in the environment, or the half blocks
+    pub fn resolve(&self) -> TermPreview {
+        if *self != TermPreview::Auto {
+            return *self;
+        }
+        let term = env::var("TERM").unwrap_or_default();
+        let program = env::var("TERM_PROGRAM").unwrap_or_default();
+        return match () {
+            _ if env::var("KITTY_WINDOW_ID").is_ok() || term.contains("kitty") || program == "ghostty" => TermPreview::Kitty,
+            _ if term.contains("sixel") || term.starts_with("mlterm") || term.starts_with("foot") || program == "WezTerm" => TermPreview::Sixel,
+            _ => TermPreview::Blocks,
+        };
+    }
+}
+
+impl ValueEnum for TermPreview {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[TermPreview::Auto, TermPreview::Blocks, TermPreview::Braille, TermPreview::Sixel, TermPreview::Kitty]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+        Some(match self {
+            TermPreview::Auto => PossibleValue::new("auto").help("Kitty or sixel graphics if the terminal declares them, half blocks otherwise"),
+            TermPreview::Blocks => PossibleValue::new("blocks").help("Unicode half blocks, a character is 1x2 pixels"),
+            TermPreview::Braille => PossibleValue::new("braille").help("Unicode braille, a character is 2x4 pixels"),
+            TermPreview::Sixel => PossibleValue::new("sixel").help("Sixel graphics in the preview scale"),
+            TermPreview::Kitty => PossibleValue::new("kitty").help("Kitty graphics protocol in the preview scale"),
+        })
+    }
+}
+
+impl Display for TermPreview {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_possible_value().unwrap().get_name())
+    }
+}
+
+impl Debug for TermPreview {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::str::FromStr for TermPreview {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        for variant in Self::value_variants() {
+            if variant.to_possible_value().unwrap().matches(s, false) {
+                return Ok(*variant);
+            }
+        }
+        Err(format!("invalid variant: {s}"))
+    }
+}
@@ -0,0 +1,47 @@
+use std::fmt::{Debug, Display, Formatter};
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TextAlign {
+    Left, Center, Right
+}
+
+impl ValueEnum for TextAlign {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[TextAlign::Left, TextAlign::Center, TextAlign::Right]
+    }
+
+    fn to_possible_value<'a>(&self) -> Option<PossibleValue> {
+        Some(match self {
+            TextAlign::Left => PossibleValue::new("left").help("The text starts at the position"),
+            TextAlign::Center => PossibleValue::new("center").help("The text is centered on the position"),
+            TextAlign::Right => PossibleValue::new("right").help("The text ends at the position"),
+        })
+    }
+}
+
+impl Display for TextAlign {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_possible_value().unwrap().get_name())
+    }
+}
+
+impl Debug for TextAlign {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl std::str::FromStr for TextAlign {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        for variant in Self::value_variants() {
+            if variant.to_possible_value().unwrap().matches(s, false) {
+                return Ok(*variant);
+            }
+        }
+        Err(format!("invalid variant: {s}"))
+    }
+}
@@ -1,9 +1,22 @@
 use std::fmt::{Debug, Display, Formatter};
 
 
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum ThresholdMode {
+    /// The dark and light values are used as is
+    Fixed,
+    /// The values are shifted to the level found by Otsu's method
+    Otsu,
+    /// Every pixel is compared with the mean of its square neighbourhood of the radius
+    Mean(u32),
+    /// Every pixel is compared with the gaussian-weighted mean of its neighbourhood of the radius
+    Gaussian(u32),
+}
+
 pub struct Threshold {
     pub dark: f32,
     pub light: f32,
+    pub mode: ThresholdMode,
 }
 
 impl Threshold {
@@ -19,11 +32,27 @@ impl Threshold {
     pub fn contains(&self, other: f32) -> bool {
         self.dark < other && other < self.light
     }
+
+    /// The same range moved from the middle to the level
+    pub fn around(&self, level: f32) -> Threshold {
+        let shift = level - 0.5;
+        Threshold {
+            dark: (self.dark + shift).clamp(0.0, 1.0),
+            light: (self.light + shift).clamp(0.0, 1.0),
+            mode: ThresholdMode::Fixed,
+        }
+    }
 }
 
 impl Display for Threshold {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}:{}", self.dark, self.light)
+        match self.mode {
+            ThresholdMode::Fixed if self.is_empty() => write!(f, "{}", percent(self.dark)),
+            ThresholdMode::Fixed => write!(f, "{}:{}", percent(self.dark), percent(self.light)),
+            ThresholdMode::Otsu => write!(f, "auto:{}", percent(self.size())),
+            ThresholdMode::Mean(radius) => write!(f, "mean:{radius}:{}", percent(self.size())),
+            ThresholdMode::Gaussian(radius) => write!(f, "gaussian:{radius}:{}", percent(self.size())),
+        }
     }
 }
 
@@ -38,6 +67,12 @@ impl Clone for Threshold {
         Threshold {
             dark: self.dark,
             light: self.light,
+            mode: self.mode,
         }
     }
 }
+
+// the values are written as they are passed
+fn percent(value: f32) -> u32 {
+    (value * 100.0).round() as u32
+}
@@ -0,0 +1,231 @@
+use image::{GrayImage, Luma, Rgb, RgbImage, RgbaImage};
+use crate::core::bitmap::{Bitmap, BlitMode};
+use crate::core::font::Font;
+use crate::core::params::font_source::FontSource;
+use crate::core::img2bm::{gray2bm, to_gray, to_scaled};
+use crate::core::params::frame_params::FrameParams;
+use crate::core::params::params::Params;
+use crate::core::params::threshold::Threshold;
+use crate::ext::range_ext::for_each;
+
+
+const SCREEN_WIDTH: u8 = 128;
+const SCREEN_HEIGHT: u8 = 64;
+const STATUS_BAR_HEIGHT: u32 = 13;
+// the screen pixels around the screen
+const BEZEL: u32 = 4;
+const BEZEL_COLOR: Rgb<u8> = Rgb([40, 40, 40]);
+// the area outside the scaled source and its transparent pixels
+const OUTSIDE_COLOR: Rgb<u8> = Rgb([128, 128, 128]);
+const SHEET_COLOR: Rgb<u8> = Rgb([255, 255, 255]);
+const LABEL_COLOR: Rgb<u8> = Rgb([0, 0, 0]);
+// the space around the cells of a sheet, in the label pixels
+const CELL_PADDING: u32 = 4;
+
+
+/// The visible pixels are black squares of the scale size on the white
+pub fn bm2preview(bitmap: &Bitmap, scale: u32) -> GrayImage {
+    let width = bitmap.width as u32;
+    let height = bitmap.height as u32;
+    let mut image = GrayImage::new(width * scale, height * scale);
+    for y in 0..height {
+        for x in 0..width {
+            // +1 because of the first byte is extra 0x00
+            let bit = bitmap.get(x, y);
+            if !bit {
+                for x in (x * scale)..(x * scale + scale) {
+                    for y in (y * scale)..(y * scale + scale) {
+                        image.put_pixel(x, y, Luma([255u8]));
+                    }
+                }
+            }
+        }
+    }
+    return image;
+}
+
+/// The bitmap on its own or on the whole screen, in the theme colors, every pixel is a scale x scale square
+pub fn styled_preview(bitmap: &Bitmap, params: &Params) -> RgbImage {
+    let theme = params.preview_theme;
+    let scale = params.preview_scale;
+    let (bitmap, margin) = match params.screen {
+        true => (on_screen(bitmap), BEZEL * scale),
+        false => (bitmap.clone(), 0),
+    };
+    let width = bitmap.width as u32 * scale + margin * 2;
+    let height = bitmap.height as u32 * scale + margin * 2;
+    // the gaps take a line of the square, so the pixels stay visible with a small scale
+    let gap = params.pixel_grid && scale > 1;
+    let mut image = RgbImage::from_pixel(width, height, BEZEL_COLOR);
+    for_each(0..bitmap.height as u32, 0..bitmap.width as u32, |x, y| {
+        let color = if bitmap.get(x, y) { theme.pixel() } else { theme.background() };
+        for dy in 0..scale {
+            for dx in 0..scale {
+                let edge = gap && (dx == scale - 1 || dy == scale - 1);
+                let pixel = if edge { theme.gap() } else { color };
+                image.put_pixel(margin + x * scale + dx, margin + y * scale + dy, pixel);
+            }
+        }
+    });
+    return image;
+}
+
+/// The animations are drawn at the bottom of the screen, the status bar is drawn over them
+fn on_screen(bitmap: &Bitmap) -> Bitmap {
+    let mut screen = Bitmap::with_size(SCREEN_WIDTH, SCREEN_HEIGHT);
+    let x = (SCREEN_WIDTH as i32 - bitmap.width as i32) / 2;
+    let y = SCREEN_HEIGHT as i32 - bitmap.height as i32;
+    screen.blit(bitmap, x, y, BlitMode::Copy);
+    // the battery at the right of the status bar
+    let right = SCREEN_WIDTH as i32 - 1;
+    screen.fill_rect(right - 25, 0, 26, STATUS_BAR_HEIGHT, false);
+    screen.draw_rect(right - 24, 2, 22, 9, true);
+    screen.fill_rect(right - 22, 4, 18, 5, true);
+    screen.fill_rect(right - 2, 4, 2, 5, true);
+    return screen;
+}
+
+/// The scaled source in color and in grayscale next to the result, labeled with the parameters they are made with
+pub fn compare(image: &RgbaImage, bitmap: &Bitmap, params: &Params, frame: &FrameParams) -> RgbImage {
+    let scaled = to_scaled(image, params, frame);
+    let gray = to_gray(image, params, frame);
+    let color = source_panel(bitmap, params.preview_scale, scaled.dimensions(), |x, y| {
+        let [r, g, b, a] = scaled.get_pixel(x, y).0;
+        Some(Rgb([r, g, b])).filter(|_| a > 0)
+    });
+    let gray = source_panel(bitmap, params.preview_scale, gray.dimensions(), |x, y| {
+        let [luma, a] = gray.get_pixel(x, y).0;
+        Some(Rgb([luma, luma, luma])).filter(|_| a > 0)
+    });
+    let cells = vec![
+        (color, format!("source\n{}, {}", params.scale_type, params.alignment)),
+        (gray, format!("grayscale\n{}", gray_label(params))),
+        (styled_preview(bitmap, params), result_label(params, &frame.threshold)),
+    ];
+    return labeled_grid(&cells, 3, label_scale(params));
+}
+
+/// The source next to the results of every threshold, the dithering is made within the threshold range
+pub fn contact_sheet(image: &RgbaImage, params: &Params, frame: &FrameParams, thresholds: &[Threshold]) -> RgbImage {
+    let gray = to_gray(image, params, frame);
+    let mut cells = Vec::new();
+    for threshold in thresholds {
+        let variant = FrameParams { threshold: threshold.clone(), key: frame.key, previous: None };
+        let bitmap = gray2bm(&gray, params, &variant);
+        if cells.is_empty() {
+            let scaled = to_scaled(image, params, frame);
+            let color = source_panel(&bitmap, params.preview_scale, scaled.dimensions(), |x, y| {
+                let [r, g, b, a] = scaled.get_pixel(x, y).0;
+                Some(Rgb([r, g, b])).filter(|_| a > 0)
+            });
+            cells.push((color, format!("source\n{}, {}\ngray {}", params.scale_type, params.alignment, gray_label(params))));
+        }
+        cells.push((styled_preview(&bitmap, params), result_label(params, threshold)));
+    }
+    let columns = (cells.len() as f32).sqrt().ceil() as usize;
+    return labeled_grid(&cells, columns, label_scale(params));
+}
+
+/// The cells in rows with their labels under them, the labels can have several lines
+pub fn labeled_grid(cells: &[(RgbImage, String)], columns: usize, scale: u32) -> RgbImage {
+    // the embedded font is always parsed
+    let font = Font::load(&FontSource::Secondary).unwrap();
+    let line_height = (font.height() as u32 + 1) * scale;
+    let padding = CELL_PADDING * scale;
+    let image_height = cells.iter().map(|it| it.0.height()).max().unwrap_or(0);
+    let label_height = cells.iter().map(|it| it.1.lines().count() as u32).max().unwrap_or(0) * line_height;
+    let cell_width = cells.iter()
+        .map(|(image, label)| {
+            let label_width = label.lines().map(|it| font.width_of(it).max(0) as u32).max().unwrap_or(0);
+            image.width().max(label_width * scale)
+        })
+        .max()
+        .unwrap_or(0);
+    let cell_height = image_height + padding + label_height;
+    let columns = columns.clamp(1, cells.len().max(1));
+    let rows = cells.len().div_ceil(columns);
+    let width = columns as u32 * (cell_width + padding) + padding;
+    let height = rows as u32 * (cell_height + padding) + padding;
+    let mut sheet = RgbImage::from_pixel(width, height, SHEET_COLOR);
+    for (index, (image, label)) in cells.iter().enumerate() {
+        let left = padding + (index % columns) as u32 * (cell_width + padding);
+        let top = padding + (index / columns) as u32 * (cell_height + padding);
+        for (x, y, pixel) in image.enumerate_pixels() {
+            sheet.put_pixel(left + x, top + y, *pixel);
+        }
+        for (line_index, line) in label.lines().enumerate() {
+            let line_top = top + image_height + padding + line_index as u32 * line_height;
+            draw_line(&mut sheet, &font, line, left, line_top, scale);
+        }
+    }
+    return sheet;
+}
+
+// the pixels of the bitmap are taken from the source pixels they are made from
+fn source_panel<F>(bitmap: &Bitmap, scale: u32, size: (u32, u32), pixel: F) -> RgbImage where F: Fn(u32, u32) -> Option<Rgb<u8>> {
+    let mut image = RgbImage::from_pixel(bitmap.width as u32 * scale, bitmap.height as u32 * scale, OUTSIDE_COLOR);
+    for_each(0..bitmap.height as u32, 0..bitmap.width as u32, |x, y| {
+        let src_x = bitmap.get_src_x(x);
+        let src_y = bitmap.get_src_y(y);
+        if src_x < 0 || src_x >= size.0 as i32 || src_y < 0 || src_y >= size.1 as i32 {
+            return;
+        }
+        let Some(color) = pixel(src_x as u32, src_y as u32) else { return };
+        for_each(y * scale..(y + 1) * scale, x * scale..(x + 1) * scale, |x, y| image.put_pixel(x, y, color));
+    });
+    return image;
+}
+
+fn draw_line(image: &mut RgbImage, font: &Font, line: &str, left: u32, top: u32, scale: u32) {
+    let baseline = font.ascent;
+    let mut origin = 0;
+    for char in line.chars() {
+        let Some(glyph) = font.glyphs.get(&char) else { continue };
+        let glyph_left = origin + glyph.dx;
+        let glyph_top = baseline - glyph.dy - glyph.height as i32;
+        for_each(0..glyph.height, 0..glyph.width, |gx, gy| {
+            let x = glyph_left + gx as i32;
+            let y = glyph_top + gy as i32;
+            if !glyph.get(gx, gy) || x < 0 || y < 0 {
+                return;
+            }
+            for_each(0..scale, 0..scale, |dx, dy| {
+                let x = left + x as u32 * scale + dx;
+                let y = top + y as u32 * scale + dy;
+                if x < image.width() && y < image.height() {
+                    image.put_pixel(x, y, LABEL_COLOR);
+                }
+            });
+        });
+        origin += glyph.advance;
+    }
+}
+
+fn gray_label(params: &Params) -> String {
+    let mut label = params.gray.to_string();
+    if let Some(sharpen) = params.sharpen {
+        label += &format!(", sharpen {sharpen}");
+    }
+    return label;
+}
+
+fn result_label(params: &Params, threshold: &Threshold) -> String {
+    let dither = match params.temporal {
+        _ if threshold.is_empty() => String::from("none"),
+        Some(change) => format!("{}, temporal {change}", params.dither),
+        None => params.dither.to_string(),
+    };
+    let mut label = format!("threshold {threshold}\ndither {dither}");
+    if let Some(edges) = params.edges {
+        label += &format!("\nedges {edges}");
+    }
+    if params.inverse {
+        label += "\ninverse";
+    }
+    return label;
+}
+
+/// The labels are half the preview pixels
+pub fn label_scale(params: &Params) -> u32 {
+    params.preview_scale.div_ceil(2).max(1)
+}
@@ -0,0 +1,49 @@
+use image::imageops::overlay;
+use image::RgbaImage;
+use crate::core::color::remove_background;
+use crate::core::params::key_color::KeyColor;
+use crate::core::params::params::Params;
+
+
+/// The still picture the source frames are composited with
+pub struct Scene {
+    pub image: RgbaImage,
+    pub x: i64,
+    pub y: i64,
+    pub above: bool,
+    pub sprite_key: Option<KeyColor>,
+}
+
+impl Scene {
+
+    pub fn load(params: &Params) -> Result<Option<Scene>, String> {
+        let Some(path) = &params.scene_path else { return Ok(None) };
+        let image = image::open(path).map_err(|it| format!("{path}: {it}"))?.to_rgba8();
+        let scene = Scene {
+            image,
+            x: params.scene_position.0,
+            y: params.scene_position.1,
+            above: params.scene_above,
+            sprite_key: params.sprite_key,
+        };
+        return Ok(Some(scene));
+    }
+
+    /// The source frame placed at the position over the scene or under it,
+    /// the result has the size of the scene
+    pub fn compose(&self, sprite: &RgbaImage) -> RgbaImage {
+        let mut sprite = sprite.clone();
+        if let Some(key) = self.sprite_key.map(|it| it.resolve(&sprite)) {
+            remove_background(&mut sprite, key.color.unwrap(), key.tolerance, false);
+        }
+        if self.above {
+            let mut result = RgbaImage::new(self.image.width(), self.image.height());
+            overlay(&mut result, &sprite, self.x, self.y);
+            overlay(&mut result, &self.image, 0, 0);
+            return result;
+        }
+        let mut result = self.image.clone();
+        overlay(&mut result, &sprite, self.x, self.y);
+        return result;
+    }
+}
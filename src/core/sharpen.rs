@@ -0,0 +1,37 @@
+use image::{GrayAlphaImage, LumaA};
+use crate::core::auto_threshold::gaussian_means;
+use crate::core::params::sharpen::Sharpen;
+
+
+pub fn sharpen(image: &GrayAlphaImage, sharpen: Sharpen) -> GrayAlphaImage {
+    let width = image.width() as i32;
+    let height = image.height() as i32;
+    let at = |x: i32, y: i32| image.get_pixel(x.clamp(0, width - 1) as u32, y.clamp(0, height - 1) as u32).0[0] as f32;
+    let (details, amount) = match sharpen {
+        Sharpen::Unsharp(radius, amount) => {
+            let blurred = gaussian_means(image, radius);
+            let details = image.pixels()
+                .zip(blurred.iter())
+                .map(|(pixel, blurred)| pixel.0[0] as f32 - blurred)
+                .collect::<Vec<f32>>();
+            (details, amount)
+        },
+        Sharpen::Laplacian(amount) => {
+            let mut details = Vec::with_capacity(image.len() / 2);
+            for y in 0..height {
+                for x in 0..width {
+                    details.push(4.0 * at(x, y) - at(x - 1, y) - at(x + 1, y) - at(x, y - 1) - at(x, y + 1));
+                }
+            }
+            (details, amount)
+        },
+    };
+    let amount = amount as f32 / 100.0;
+    let mut result = GrayAlphaImage::new(image.width(), image.height());
+    for (x, y, pixel) in image.enumerate_pixels() {
+        let [luminance, alpha] = pixel.0;
+        let value = luminance as f32 + amount * details[(y * image.width() + x) as usize];
+        result.put_pixel(x, y, LumaA([value.round().clamp(0.0, 255.0) as u8, alpha]));
+    }
+    return result;
+}
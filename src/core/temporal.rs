@@ -0,0 +1,111 @@
+use image::{GrayAlphaImage, LumaA};
+use crate::core::bitmap::Bitmap;
+use crate::ext::range_ext::for_each;
+
+
+pub struct PreviousFrame {
+    /// The luminance of every pixel when its dot was dithered the last time
+    pub gray: GrayAlphaImage,
+    pub bitmap: Bitmap,
+}
+
+impl PreviousFrame {
+
+    /// The reference for the next frame, the luminance is only updated where the pixels were dithered again,
+    /// so a slow change adds up until it's over the limit
+    pub fn update(previous: Option<PreviousFrame>, gray: GrayAlphaImage, bitmap: Bitmap, change: u8) -> PreviousFrame {
+        let Some(mut previous) = previous.filter(|it| it.gray.dimensions() == gray.dimensions()) else {
+            return PreviousFrame { gray, bitmap };
+        };
+        let change = to_luminance(change);
+        for (reference, pixel) in previous.gray.pixels_mut().zip(gray.pixels()) {
+            if is_changed(pixel, reference, change) {
+                *reference = *pixel;
+            }
+        }
+        previous.bitmap = bitmap;
+        return previous;
+    }
+
+    /// Sets the previous dots in the unchanged area before the dithering,
+    /// so the new dots are placed around them
+    pub fn seed(&self, gray: &GrayAlphaImage, bitmap: &mut Bitmap, change: u8, inverse: bool) {
+        self.for_each_unchanged(gray, bitmap, change, |bitmap, x, y, previous| {
+            if previous != inverse {
+                bitmap.set(x, y);
+            }
+        });
+    }
+
+    /// Restores the previous pixels in the unchanged area after the dithering
+    pub fn keep(&self, gray: &GrayAlphaImage, bitmap: &mut Bitmap, change: u8) {
+        self.for_each_unchanged(gray, bitmap, change, |bitmap, x, y, previous| {
+            bitmap.set_to(x, y, previous);
+        });
+    }
+
+    fn for_each_unchanged<F>(
+        &self,
+        gray: &GrayAlphaImage,
+        bitmap: &mut Bitmap,
+        change: u8,
+        mut action: F,
+    ) where F: FnMut(&mut Bitmap, u32, u32, /*previous:*/bool) {
+        if gray.dimensions() != self.gray.dimensions() {
+            return;
+        }
+        let change = to_luminance(change);
+        for_each(0..bitmap.height as u32, 0..bitmap.width as u32, |x, y| {
+            let src_x = bitmap.get_src_x(x);
+            let src_y = bitmap.get_src_y(y);
+            if src_x < 0 || src_x >= gray.width() as i32 || src_y < 0 || src_y >= gray.height() as i32 {
+                return;
+            }
+            let pixel = gray.get_pixel(src_x as u32, src_y as u32);
+            let reference = self.gray.get_pixel(src_x as u32, src_y as u32);
+            if is_changed(pixel, reference, change) {
+                return;
+            }
+            let previous = self.bitmap.get(x, y);
+            action(bitmap, x, y, previous);
+        });
+    }
+}
+
+fn to_luminance(percent: u8) -> i32 {
+    percent as i32 * 255 / 100
+}
+
+/// The transparent pixels are always dithered again
+fn is_changed(pixel: &LumaA<u8>, reference: &LumaA<u8>, change: i32) -> bool {
+    let [luminance, alpha] = pixel.0;
+    let [reference_luminance, reference_alpha] = reference.0;
+    return alpha == 0 || alpha != reference_alpha || (luminance as i32 - reference_luminance as i32).abs() > change;
+}
+
+#[cfg(test)]
+mod tests {
+    use image::{GrayAlphaImage, LumaA};
+    use crate::core::bitmap::Bitmap;
+    use crate::core::temporal::PreviousFrame;
+
+    /// A fade of 8 per frame under the limit of 5% is dithered again every second frame, not frozen
+    #[test]
+    fn slow_fade_is_dithered_again() {
+        let change = 5;
+        let mut previous = None::<PreviousFrame>;
+        let mut dithered = Vec::new();
+        for step in 0..6u8 {
+            let gray = GrayAlphaImage::from_pixel(4, 4, LumaA([50 + step * 8, 255]));
+            // the new dots are all set, the kept ones of the previous frame are clear
+            let mut bitmap = Bitmap::with_size(4, 4);
+            bitmap.fill_rect(0, 0, 4, 4, true);
+            if let Some(previous) = &previous {
+                previous.keep(&gray, &mut bitmap, change);
+            }
+            dithered.push(bitmap.get(0, 0));
+            previous = Some(PreviousFrame::update(previous, gray, Bitmap::with_size(4, 4), change));
+        }
+        assert_eq!(dithered, [true, false, true, false, true, false]);
+    }
+}
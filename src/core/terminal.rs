@@ -0,0 +1,149 @@
+use std::io::{stdout, Write};
+use std::thread::sleep;
+use std::time::Duration;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use image::codecs::png::PngEncoder;
+use image::{ExtendedColorType, GrayImage, ImageEncoder};
+use crate::core::bitmap::Bitmap;
+use crate::core::meta::FrameData;
+use crate::core::params::term_preview::TermPreview;
+use crate::core::preview::bm2preview;
+
+
+const KITTY_CHUNK: usize = 4096;
+const KITTY_IMAGE_ID: u32 = 2187;
+// the least cell height to reserve the rows for the graphics with
+const MIN_CELL_HEIGHT: u32 = 12;
+
+/// The bitmap in the mode, the graphics ones are drawn in the scale
+pub fn render(bitmap: &Bitmap, mode: TermPreview, scale: u32) -> String {
+    match mode {
+        TermPreview::Auto | TermPreview::Blocks => half_blocks(bitmap, 1),
+        TermPreview::Braille => braille(bitmap),
+        TermPreview::Sixel => sixel(&bm2preview(bitmap, scale)),
+        TermPreview::Kitty => kitty(&bm2preview(bitmap, scale)),
+    }
+}
+
+/// Draws the frames one over another with their durations, once
+pub fn play(frames: &[Bitmap], data: &[FrameData], mode: TermPreview, scale: u32, speed: f32) {
+    let Some(first) = frames.first() else { return };
+    let rendered = frames.iter().map(|it| render(it, mode, scale)).collect::<Vec<String>>();
+    let rows = match mode {
+        TermPreview::Sixel | TermPreview::Kitty => (first.height as u32 * scale).div_ceil(MIN_CELL_HEIGHT) + 1,
+        _ => rendered[0].lines().count() as u32,
+    };
+    let mut out = stdout();
+    // the rows are reserved, so the terminal doesn't scroll and the saved cursor stays in place
+    print!("{}\x1b[{rows}A\x1b7", "\n".repeat(rows as usize));
+    for fd in data {
+        print!("\x1b8{}", rendered[fd.index]);
+        out.flush().unwrap();
+        sleep(Duration::from_secs_f32(fd.duration / speed / 1000.0));
+    }
+    println!();
+}
+
+/// The visible pixels drawn with the half block characters, a character is two pixels high,
+/// the step skips the pixels to make it smaller
+pub fn half_blocks(bitmap: &Bitmap, step: u32) -> String {
+    let step = step.max(1);
+    let get = |x: u32, y: u32| y < bitmap.height as u32 && bitmap.get(x, y);
+    let mut result = String::new();
+    for y in (0..bitmap.height as u32).step_by(2 * step as usize) {
+        for x in (0..bitmap.width as u32).step_by(step as usize) {
+            result.push(match (get(x, y), get(x, y + step)) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            });
+        }
+        result.push('\n');
+    }
+    return result;
+}
+
+/// The visible pixels drawn with the braille dots, a character is 2x4 pixels
+pub fn braille(bitmap: &Bitmap) -> String {
+    // the dot bits of the columns from the top
+    const DOTS: [[u32; 4]; 2] = [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+    let get = |x: u32, y: u32| x < bitmap.width as u32 && y < bitmap.height as u32 && bitmap.get(x, y);
+    let mut result = String::new();
+    for y in (0..bitmap.height as u32).step_by(4) {
+        for x in (0..bitmap.width as u32).step_by(2) {
+            let mut dots = 0;
+            for (dx, column) in DOTS.iter().enumerate() {
+                for (dy, dot) in column.iter().enumerate() {
+                    if get(x + dx as u32, y + dy as u32) {
+                        dots |= dot;
+                    }
+                }
+            }
+            result.push(char::from_u32(0x2800 + dots).unwrap());
+        }
+        result.push('\n');
+    }
+    return result;
+}
+
+/// Two colors sixel, the bands are 6 pixels high with a run of every color
+pub fn sixel(image: &GrayImage) -> String {
+    let (width, height) = image.dimensions();
+    let mut result = format!("\x1bPq\"1;1;{width};{height}#0;2;100;100;100#1;2;0;0;0");
+    for band in (0..height).step_by(6) {
+        for (color, luma) in [(0, 255u8), (1, 0u8)] {
+            result += &format!("#{color}");
+            let sixels = (0..width).map(|x| {
+                let bits = (0..6)
+                    .filter(|dy| band + dy < height && image.get_pixel(x, band + dy).0[0] == luma)
+                    .fold(0u8, |bits, dy| bits | (1 << dy));
+                (63 + bits) as char
+            });
+            result += &run_length(sixels);
+            result.push('$');
+        }
+        result.push('-');
+    }
+    result += "\x1b\\\n";
+    return result;
+}
+
+fn run_length(chars: impl Iterator<Item = char>) -> String {
+    let mut result = String::new();
+    let mut chars = chars.peekable();
+    while let Some(char) = chars.next() {
+        let mut count = 1;
+        while chars.peek() == Some(&char) {
+            chars.next();
+            count += 1;
+        }
+        match count {
+            1..=3 => (0..count).for_each(|_| result.push(char)),
+            _ => result += &format!("!{count}{char}"),
+        }
+    }
+    return result;
+}
+
+/// PNG in the kitty graphics protocol, the same image id replaces the previous frame
+pub fn kitty(image: &GrayImage) -> String {
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png)
+        .write_image(image, image.width(), image.height(), ExtendedColorType::L8)
+        .unwrap();
+    let data = STANDARD.encode(png);
+    let chunks = data.as_bytes().chunks(KITTY_CHUNK).collect::<Vec<&[u8]>>();
+    let mut result = String::new();
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = if i + 1 < chunks.len() { 1 } else { 0 };
+        let control = match i {
+            0 => format!("a=T,f=100,q=2,i={KITTY_IMAGE_ID},m={more}"),
+            _ => format!("m={more}"),
+        };
+        result += &format!("\x1b_G{control};{}\x1b\\", std::str::from_utf8(chunk).unwrap());
+    }
+    result.push('\n');
+    return result;
+}
@@ -0,0 +1,61 @@
+use crate::core::bitmap::Bitmap;
+use crate::core::font::Font;
+use crate::core::params::text_align::TextAlign;
+
+
+const FRAME_NUMBER: &str = "%n";
+const BOX_PADDING: i32 = 1;
+
+/// The text drawn over the output frames
+pub struct Text {
+    pub font: Font,
+    /// The frames take the lines one by one in a cycle
+    pub lines: Vec<String>,
+    pub x: i32,
+    pub y: i32,
+    pub align: TextAlign,
+    pub boxed: bool,
+    /// Pixels per frame the text scrolls from right to left across the whole width
+    pub marquee: Option<u32>,
+}
+
+impl Text {
+
+    /// The frame is the index of the output frame starting with 0
+    pub fn draw(&self, bitmap: &mut Bitmap, frame: usize) {
+        let line = &self.lines[frame % self.lines.len()];
+        let line = line.replace(FRAME_NUMBER, &(frame + 1).to_string());
+        let width = self.font.width_of(&line);
+        let x = match self.marquee {
+            Some(speed) => {
+                let path = bitmap.width as i64 + width as i64;
+                bitmap.width as i32 - (frame as i64 * speed as i64 % path.max(1)) as i32
+            },
+            None => match self.align {
+                TextAlign::Left => self.x,
+                TextAlign::Center => self.x - width / 2,
+                TextAlign::Right => self.x - width,
+            },
+        };
+        if self.boxed {
+            let box_width = (width + BOX_PADDING * 2).max(0) as u32;
+            let box_height = (self.font.height() + BOX_PADDING * 2).max(0) as u32;
+            bitmap.fill_rect(x - BOX_PADDING, self.y - BOX_PADDING, box_width, box_height, true);
+        }
+        let baseline = self.y + self.font.ascent;
+        let mut origin = x;
+        for char in line.chars() {
+            let Some(glyph) = self.font.glyphs.get(&char) else { continue };
+            let left = origin + glyph.dx;
+            let top = baseline - glyph.dy - glyph.height as i32;
+            for gy in 0..glyph.height {
+                for gx in 0..glyph.width {
+                    if glyph.get(gx, gy) {
+                        bitmap.put(left + gx as i32, top + gy as i32, !self.boxed);
+                    }
+                }
+            }
+            origin += glyph.advance;
+        }
+    }
+}
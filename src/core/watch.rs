@@ -0,0 +1,54 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+// an editor can write a file in several steps, it's read once they stop
+const DEBOUNCE: Duration = Duration::from_millis(400);
+
+/// The files and their modification times, None for a missing one
+pub type Stamps = Vec<(PathBuf, Option<SystemTime>)>;
+
+/// The files of the paths and of the directories among them, but the ones in the excluded directory
+pub fn stamps(paths: &[PathBuf], excluded: Option<&Path>) -> Stamps {
+    let mut stamps = Stamps::new();
+    for path in paths {
+        add(path, excluded, &mut stamps);
+    }
+    stamps.sort();
+    return stamps;
+}
+
+/// Returns once the files have changed and stayed the same for the debounce time, with their new stamps
+pub fn wait_for_change(paths: &[PathBuf], excluded: Option<&Path>, stamps: &Stamps) -> Stamps {
+    let mut current = stamps.clone();
+    while current == *stamps {
+        sleep(POLL_INTERVAL);
+        current = self::stamps(paths, excluded);
+    }
+    loop {
+        sleep(DEBOUNCE);
+        let next = self::stamps(paths, excluded);
+        if next == current {
+            return current;
+        }
+        current = next;
+    }
+}
+
+fn add(path: &Path, excluded: Option<&Path>, stamps: &mut Stamps) {
+    if excluded.is_some_and(|it| path.starts_with(it)) {
+        return;
+    }
+    if !path.is_dir() {
+        let modified = fs::metadata(path).and_then(|it| it.modified()).ok();
+        stamps.push((path.to_path_buf(), modified));
+        return;
+    }
+    let Ok(entries) = fs::read_dir(path) else { return };
+    for entry in entries.filter_map(|it| it.ok()) {
+        add(&entry.path(), excluded, stamps);
+    }
+}
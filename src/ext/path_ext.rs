@@ -3,8 +3,14 @@ use std::path::PathBuf;
 
 pub const EXT_PICTURE: [&str; 3] = ["png", "jpg", "jpeg"];
 pub const EXT_BM: &str = "bm";
+pub const EXT_BMX: &str = "bmx";
+pub const EXT_XBM: &str = "xbm";
+pub const EXT_PBM: &str = "pbm";
+pub const EXT_BIN: &str = "bin";
 pub const EXT_PNG: &str = "png";
 pub const EXT_GIF: &str = "gif";
+pub const EXT_H: &str = "h";
+pub const EXT_C: &str = "c";
 
 pub trait PathExt {
     fn to_string(&self) -> String;
@@ -1,25 +1,47 @@
-mod core;
-mod ext;
-
-use crate::core::bitmap::Bitmap;
-use crate::core::img2bm::img2bm;
-use crate::core::meta::{get_manifest, get_meta, FrameData};
-use crate::core::params::params::{FileType, Params};
-use crate::ext::unit_ext::UnitUtil;
+use img2fbm::core::asset_pack::{targets, TargetKind};
+use img2fbm::core::bitmap::Bitmap;
+use img2fbm::core::cache::{key, Cache};
+use img2fbm::core::check::{check, Severity};
+use img2fbm::core::dolphin::{parse_meta, MANIFEST_FILE, META_FILE};
+use img2fbm::core::auto_threshold::{add_to_histogram, otsu_level};
+use img2fbm::core::img2bm::{gray2bm, img2bm, pixels2bm, to_gray};
+use img2fbm::core::meta::{get_manifest, get_meta, get_order, FrameData};
+use img2fbm::core::c_export::c_source;
+use img2fbm::core::formats::{encode, to_bmx_meta};
+use img2fbm::core::heatshrink::pack;
+use img2fbm::core::info::info;
+use img2fbm::core::preview::{compare, contact_sheet, label_scale, labeled_grid, styled_preview};
+use img2fbm::core::terminal::{play, render};
+use img2fbm::core::params::output_format::OutputFormat;
+use img2fbm::core::params::params::{FileType, Params};
+use img2fbm::core::params::frame_params::FrameParams;
+use img2fbm::core::params::task::Task;
+use img2fbm::core::params::threshold::{Threshold, ThresholdMode};
+use img2fbm::core::temporal::PreviousFrame;
+use img2fbm::core::scene::Scene;
+use img2fbm::core::watch::{stamps, wait_for_change};
+use img2fbm::ext::unit_ext::UnitUtil;
 use image::codecs::gif::{GifDecoder, GifEncoder, Repeat};
-use image::{AnimationDecoder, ColorType, Delay, DynamicImage, Frame, GrayImage, ImageFormat, Luma};
+use image::codecs::png::PngDecoder;
+use image::{AnimationDecoder, ColorType, Delay, DynamicImage, Frame, ImageError, ImageFormat, RgbImage, RgbaImage};
 use indicatif::{ProgressBar, ProgressStyle};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
 use std::fs;
 use std::fs::{create_dir_all, File, OpenOptions};
 use std::hash::{Hash, Hasher};
 use std::io::{stdin, BufReader, Write};
-use std::path::Path;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::process::exit;
 
 
 fn main() {
     if std::env::args().len() > 1 {
-        work(Params::try_parse().unwrap());
+        if !run(Task::try_parse().unwrap_or_else(|it| it.exit())) {
+            exit(1);
+        }
     } else {
         Params::print_help();
         looped_work();
@@ -31,9 +53,9 @@ fn looped_work() {
     let mut line = String::new();
     stdin().read_line(&mut line).unwrap();
     if line.trim().len() > 1 {
-        match Params::try_parse_from(line) {
+        match Task::try_parse_from(line) {
             Ok(o) => {
-                work(o);
+                run(o);
                 println!("it's done! another one?")
             },
             Err(msg) => println!("{msg}"),
@@ -42,25 +64,140 @@ fn looped_work() {
     }
 }
 
-fn work(params: Params) {
-    match params.file_type {
-        FileType::Picture => from_picture(&params),
-        FileType::Gif => from_gif(&params),
+/// Whether it's done without errors
+fn run(task: Task) -> bool {
+    match task {
+        Task::Convert(params) if params.watch => watch(*params),
+        Task::Convert(params) => if let Err(msg) = work(*params) {
+            println!("{msg}");
+            return false;
+        },
+        Task::Check(dir) => return check_dolphin(&dir),
+        Task::Info(path, json) => return print_info(&path, json),
+    }
+    return true;
+}
+
+fn check_dolphin(dir: &Path) -> bool {
+    let issues = check(dir);
+    for issue in &issues {
+        println!("{issue}");
+    }
+    let errors = issues.iter().filter(|it| it.severity == Severity::Error).count();
+    println!("{errors} errors, {} warnings", issues.len() - errors);
+    return errors == 0;
+}
+
+fn print_info(path: &Path, json: bool) -> bool {
+    let infos = match info(path) {
+        Ok(infos) => infos,
+        Err(msg) => {
+            println!("{msg}");
+            return false;
+        },
+    };
+    match () {
+        _ if !json => infos.iter().for_each(|it| println!("{it}")),
+        _ if path.join(MANIFEST_FILE).is_file() => println!("{}", serde_json::to_string_pretty(&infos).unwrap()),
+        _ => println!("{}", serde_json::to_string_pretty(&infos[0]).unwrap()),
+    }
+    return true;
+}
+
+/// Converts on every change until the process is stopped, the failures are reported
+fn watch(mut params: Params) {
+    let args = params.args().to_vec();
+    // a failed conversion is reported in a line instead of a backtrace
+    panic::set_hook(Box::new(|info| println!("the conversion failed: {}", panic_message(info))));
+    loop {
+        let paths = params.watched_paths();
+        let pack = params.asset_pack.clone().map(PathBuf::from);
+        // the files saved while converting are picked up by the next conversion
+        let mut stamps = stamps(&paths, pack.as_deref());
+        if params.term_preview.is_some() {
+            // the terminal preview is drawn on the clear screen every time
+            print!("\x1b[2J\x1b[H");
+        }
+        if let Ok(Err(msg)) = panic::catch_unwind(AssertUnwindSafe(|| work(params))) {
+            println!("{msg}");
+        }
+        println!("watching for changes, press Ctrl+C to stop");
+        params = loop {
+            stamps = wait_for_change(&paths, pack.as_deref(), &stamps);
+            match Task::try_parse_args(args.clone()) {
+                Ok(Task::Convert(params)) => break *params,
+                Ok(_) => println!("not a conversion"),
+                Err(msg) => println!("{msg}"),
+            }
+        };
+    }
+}
+
+fn panic_message(info: &panic::PanicHookInfo) -> String {
+    let payload = info.payload();
+    return match (payload.downcast_ref::<&str>(), payload.downcast_ref::<String>()) {
+        (Some(msg), _) => msg.to_string(),
+        (_, Some(msg)) => msg.clone(),
+        _ => String::from("unknown error"),
+    };
+}
+
+/// The error of a source that can't be read
+fn work(params: Params) -> Result<(), String> {
+    if params.asset_pack.is_some() {
+        return build_asset_pack(&params);
     }
+    if !params.sweep.is_empty() {
+        return sweep(&params);
+    }
+    return match params.file_type {
+        FileType::Picture => from_picture(&params),
+        FileType::Gif | FileType::Apng => from_animation(&params),
+        FileType::Folder => from_folder(&params),
+    };
 }
 
-fn from_picture(params: &Params) {
-    let image = image::open(params.path_src.clone()).unwrap().to_rgba8();
-    let bitmap = img2bm(&image, &params);
+fn from_picture(params: &Params) -> Result<(), String> {
+    let image = load_picture(params)?;
+    let frame = FrameParams::from(params, &image);
+    let bitmap = picture_bitmap(params, &image, &frame);
 
     if !params.only_preview {
-        let mut file_dst = File::create(params.picture_path_bm.clone()).unwrap();
-        file_dst.write_all(bitmap.bytes.as_slice()).unwrap();
+        let mut file_dst = File::create(params.picture_path_dst.clone()).unwrap();
+        file_dst.write_all(&encode(&bitmap, params.format, params.layout, &params.c_name)).unwrap();
+        write_c_array(params, std::slice::from_ref(&bitmap), &[0], 0);
+    }
+    if let Some(mode) = params.term_preview {
+        print!("{}", render(&bitmap, mode, params.preview_scale));
     }
     if params.preview {
-        let preview = bm2preview(&bitmap, params.preview_scale);
+        let preview = styled_preview(&bitmap, params);
         save_preview(&preview, params.preview_picture_path.as_str());
     }
+    if params.compare {
+        save_preview(&compare(&image, &bitmap, params, &frame), params.compare_picture_path.as_str());
+    }
+    if !params.contact_sheet.is_empty() {
+        let sheet = contact_sheet(&image, params, &frame, &params.contact_sheet);
+        save_preview(&sheet, params.contact_sheet_path.as_str());
+    }
+    return Ok(());
+}
+
+fn load_picture(params: &Params) -> Result<RgbaImage, String> {
+    let image = image::open(&params.path_src).map_err(|it| format!("{}: {it}", params.path_src))?.to_rgba8();
+    return match Scene::load(params)? {
+        None => Ok(image),
+        Some(scene) => Ok(scene.compose(&image)),
+    };
+}
+
+fn picture_bitmap(params: &Params, image: &RgbaImage, frame: &FrameParams) -> Bitmap {
+    let mut bitmap = img2bm(image, params, frame);
+    if let Some(text) = &params.text {
+        text.draw(&mut bitmap, 0);
+    }
+    return bitmap;
 }
 
 fn new_progress(length: usize, prefix: &str) -> ProgressBar {
@@ -75,43 +212,80 @@ fn new_progress(length: usize, prefix: &str) -> ProgressBar {
     return progressbar;
 }
 
-fn from_gif(params: &Params) {
-    let mut preview_frames = Vec::<GrayImage>::new();
-    if !params.only_preview {
+fn decode_frames(params: &Params) -> Result<Vec<Frame>, String> {
+    let error = |it: ImageError| format!("{}: {it}", params.path_src);
+    let file = File::open(&params.path_src).map_err(|it| format!("{}: {it}", params.path_src))?;
+    let reader = BufReader::new(file);
+    let frames = match params.file_type {
+        FileType::Apng => PngDecoder::new(reader).and_then(|it| it.apng()).map_err(error)?.into_frames(),
+        _ => GifDecoder::new(reader).map_err(error)?.into_frames(),
+    };
+    let frames = frames.collect_frames().map_err(error)?;
+    let frames = match &params.recipe.animation.frames {
+        None => frames,
+        Some(picked) => picked.iter().filter_map(|&it| frames.get(it).cloned()).collect(),
+    };
+    if frames.len() <= params.cut.start + params.cut.end {
+        return Err(format!("{}: no frames left after the cut", params.path_src));
+    }
+    let max_index = frames.len() - 1 - params.cut.end;
+    let frames = frames.into_iter()
+        .enumerate()
+        .filter(|&(i, _)| { i >= params.cut.start && i <= max_index })
+        .map(|(_, it)| it);
+    return match Scene::load(params)? {
+        None => Ok(frames.collect()),
+        Some(scene) => Ok(frames
+            .map(|it| Frame::from_parts(scene.compose(it.buffer()), 0, 0, it.delay()))
+            .collect()),
+    };
+}
+
+fn from_animation(params: &Params) -> Result<(), String> {
+    let frames = decode_frames(params)?;
+    let mut preview_frames = Vec::<RgbImage>::new();
+    let mut compare_frames = Vec::<RgbImage>::new();
+    if !params.only_preview && params.format != OutputFormat::Raw {
         create_dir_all(params.dolphin_anim_path.as_str()).unwrap();
     }
-    let file = File::open(params.path_src.clone()).unwrap();
-    let reader = BufReader::new(file);
-    let decoder = GifDecoder::new(reader).unwrap();
     let mut hashes = Vec::<u64>::new();
+    let mut unique = Vec::<Bitmap>::new();
     let mut data = Vec::<FrameData>::new();
     let mut min_duration = -1f32;
+    // the icons frames are written in the play order after all
+    let per_frame = params.format != OutputFormat::Raw && !params.format.in_play_order();
 
-    let frames = decoder.into_frames().collect_frames().unwrap();
-    let min_index = params.cut.start;
-    let max_index = frames.len() - 1 - params.cut.end;
-    let bar = new_progress(max_index + 1 - min_index, "Converting...");
-    let frames_iter = frames.into_iter()
-        .enumerate()
-        .filter(|&(i, _)| { i >= min_index && i <= max_index })
-        .map(|(_, it)| it);
-    for frame in frames_iter {
+    let bar = new_progress(frames.len(), "Converting...");
+    let mut frame_params = FrameParams::from(params, frames.first().unwrap().buffer());
+    frame_params.threshold = gif_threshold(params, &frame_params, &frames);
+    if !params.contact_sheet.is_empty() {
+        let sheet = contact_sheet(frames[0].buffer(), params, &frame_params, &params.contact_sheet);
+        save_preview(&sheet, params.contact_sheet_path.as_str());
+    }
+    for (frame_index, frame) in frames.into_iter().enumerate() {
         // todo use rayon
         let image = frame.buffer().to_owned();
-        let bitmap = img2bm(&image, &params);
+        let bitmap = frame_bitmap(params, &mut frame_params, &image, frame_index);
+        if params.compare {
+            compare_frames.push(compare(&image, &bitmap, params, &frame_params));
+        }
 
         let mut hasher = DefaultHasher::new();
         bitmap.hash(&mut hasher);
         let hash = hasher.finish();
         let index = hashes.iter().position(|&it| it == hash).unwrap_or_else(|| {
             let index = hashes.len();
-            if !params.only_preview {
-                let mut file_dst = File::create(params.path_bm(index)).unwrap();
-                file_dst.write_all(bitmap.bytes.as_slice()).unwrap();
+            if !params.only_preview && per_frame {
+                let mut file_dst = File::create(params.path_frame(index)).unwrap();
+                let name = format!("{}_{index}", params.c_name);
+                file_dst.write_all(&encode(&bitmap, params.format, params.layout, &name)).unwrap();
             }
             hashes.push(hash);
+            if params.c_array.is_some() || !per_frame || params.term_preview.is_some() {
+                unique.push(bitmap.clone());
+            }
             if params.preview {
-                preview_frames.push(bm2preview(&bitmap, params.preview_scale));
+                preview_frames.push(styled_preview(&bitmap, params));
             }
             index
         });
@@ -129,29 +303,325 @@ fn from_gif(params: &Params) {
         f_data.duration = (f_data.duration / min_duration).round() * min_duration;
     }
     if !params.only_preview {
-        let meta = get_meta(params.height, &data);
+        let (order, frame_rate) = get_order(&data);
+        write_c_array(params, &unique, &order, frame_rate);
+        if params.format == OutputFormat::Raw {
+            let bytes = order.iter()
+                .flat_map(|&it| encode(&unique[it], params.format, params.layout, &params.c_name))
+                .collect::<Vec<u8>>();
+            fs::write(params.picture_path_dst.clone(), bytes).unwrap();
+        }
+        if params.format.in_play_order() {
+            for (i, &it) in order.iter().enumerate() {
+                let bytes = match params.format {
+                    OutputFormat::Bmx => pack(unique[it].data()),
+                    _ => encode(&unique[it], params.format, params.layout, &params.c_name),
+                };
+                fs::write(params.path_icon_frame(i), bytes).unwrap();
+            }
+            let frame_rate = match params.format {
+                OutputFormat::Bmx => to_bmx_meta(params.width, params.height, frame_rate, order.len()),
+                _ => frame_rate.to_string().into_bytes(),
+            };
+            fs::write(params.frame_rate_path.clone(), frame_rate).unwrap();
+        }
+    }
+    if !params.only_preview && matches!(params.format, OutputFormat::Bm | OutputFormat::Png) {
+        let meta = get_meta(params.width, params.height, &data, &params.recipe);
         fs::write(params.meta_path.clone(), meta).unwrap();
         if params.with_manifest {
             write_manifest(&params);
         }
     }
     if params.preview {
-        bm2preview_gif(&params, &data, &preview_frames)
+        bm2preview_gif(params, &params.preview_gif_path, &data, &preview_frames)
+    }
+    if params.compare {
+        // every source frame is compared, the duplicates too
+        let data = data.iter()
+            .enumerate()
+            .map(|(index, it)| FrameData { index, duration: it.duration })
+            .collect::<Vec<FrameData>>();
+        bm2preview_gif(params, &params.compare_gif_path, &data, &compare_frames);
+    }
+    if let Some(mode) = params.term_preview {
+        play(&unique, &data, mode, params.preview_scale, params.speed);
+    }
+    return Ok(());
+}
+
+/// The bitmap of the GIF frame, the frame params keep the key and the temporal reference for the next one
+fn frame_bitmap(
+    params: &Params,
+    frame_params: &mut FrameParams,
+    image: &RgbaImage,
+    frame_index: usize,
+) -> Bitmap {
+    if params.frame_key {
+        frame_params.key = params.key.map(|it| it.resolve(image));
+    }
+    let gray = to_gray(image, params, frame_params);
+    let mut bitmap = gray2bm(&gray, params, frame_params);
+    if let Some(change) = params.temporal {
+        let previous = frame_params.previous.take();
+        frame_params.previous = Some(PreviousFrame::update(previous, gray, bitmap.clone(), change));
+    }
+    if let Some(text) = &params.text {
+        text.draw(&mut bitmap, frame_index);
+    }
+    return bitmap;
+}
+
+fn sweep(params: &Params) -> Result<(), String> {
+    let variants = params.sweep_variants().map_err(|it| it.to_string())?;
+    let columns = (variants.len() as f32).sqrt().ceil() as usize;
+    let scale = label_scale(params);
+    match params.file_type {
+        FileType::Picture => {
+            let image = load_picture(params)?;
+            let cells = variants.iter()
+                .map(|(label, variant)| {
+                    let bitmap = picture_bitmap(variant, &image, &FrameParams::from(variant, &image));
+                    (styled_preview(&bitmap, variant), label.clone())
+                })
+                .collect::<Vec<(RgbImage, String)>>();
+            save_preview(&labeled_grid(&cells, columns, scale), params.sweep_picture_path.as_str());
+        },
+        _ => {
+            let frames = decode_frames(params)?;
+            let bar = new_progress(variants.len() * frames.len(), "Sweeping...");
+            let converted = variants.iter()
+                .map(|(_, variant)| {
+                    let mut frame_params = FrameParams::from(variant, frames.first().unwrap().buffer());
+                    frame_params.threshold = gif_threshold(variant, &frame_params, &frames);
+                    frames.iter().enumerate()
+                        .map(|(index, frame)| {
+                            bar.inc(1);
+                            frame_bitmap(variant, &mut frame_params, frame.buffer(), index)
+                        })
+                        .collect::<Vec<Bitmap>>()
+                })
+                .collect::<Vec<Vec<Bitmap>>>();
+            bar.finish();
+            let grids = (0..frames.len())
+                .map(|index| {
+                    let cells = variants.iter().zip(&converted)
+                        .map(|((label, variant), bitmaps)| (styled_preview(&bitmaps[index], variant), label.clone()))
+                        .collect::<Vec<(RgbImage, String)>>();
+                    labeled_grid(&cells, columns, scale)
+                })
+                .collect::<Vec<RgbImage>>();
+            let data = frames.iter()
+                .enumerate()
+                .map(|(index, it)| FrameData::from(index, &it.delay()))
+                .collect::<Vec<FrameData>>();
+            bm2preview_gif(params, &params.sweep_gif_path, &data, &grids);
+        },
+    }
+    if params.sweep_files {
+        for (label, variant) in variants {
+            println!("{}", label.replace('\n', ", "));
+            work(variant)?;
+        }
+    }
+    return Ok(());
+}
+
+/// The targets that fail are reported and left as they are, the rest are built
+fn build_asset_pack(params: &Params) -> Result<(), String> {
+    let pack_path = params.asset_pack.clone().unwrap();
+    let targets = targets(Path::new(&params.path_src))?;
+    let mut cache = Cache::load(Path::new(&pack_path));
+    let names = targets.iter().map(|it| it.name.clone()).collect::<HashSet<String>>();
+    let mut anims = 0;
+    for target in targets {
+        let (dir, name) = target.split();
+        let dir = format!("{pack_path}{dir}");
+        let (format, size) = match target.kind {
+            TargetKind::Icon(width, height) => (OutputFormat::Bmx, (width, height)),
+            TargetKind::Anim => (OutputFormat::Bm, (params.width, params.height)),
+        };
+        let mut asset = match params.for_asset(&target.source, format, size, &dir, &name) {
+            Ok(asset) => asset,
+            Err(msg) => {
+                print!("{}: {msg}", target.name);
+                continue;
+            },
+        };
+        if let TargetKind::Anim = target.kind {
+            if let FileType::Picture = asset.file_type {
+                println!("{}: an animation needs a gif or apng", target.name);
+                continue;
+            }
+            // the manifest of the previous build is replaced
+            asset.replace_manifest = anims == 0;
+            anims += 1;
+        }
+        let output = match asset.file_type {
+            FileType::Picture => asset.picture_path_dst.clone(),
+            _ => asset.dolphin_anim_path.clone(),
+        };
+        let output = output.strip_prefix(&pack_path).unwrap_or(&output).trim_end_matches('/').to_string();
+        // the config files are in the options
+        let files = asset.input_paths();
+        let files = files.iter().map(PathBuf::as_path).collect::<Vec<_>>();
+        let key = key(&files, &[&asset.options(), &format!("{size:?}"), env!("CARGO_PKG_VERSION")]);
+        if cache.is_fresh(&target.name, key, &output) {
+            println!("{} is up to date", target.name);
+            // the manifest is written anew
+            if let TargetKind::Anim = target.kind {
+                write_manifest(&asset);
+            }
+            continue;
+        }
+        println!("{}", target.name);
+        create_dir_all(dir).unwrap();
+        if let Err(msg) = work(asset) {
+            println!("{}: {msg}", target.name);
+            continue;
+        }
+        cache.insert(&target.name, key, &output);
+    }
+    for name in cache.clean(&names) {
+        println!("{name} is removed");
+    }
+    cache.save();
+    return Ok(());
+}
+
+fn from_folder(params: &Params) -> Result<(), String> {
+    let source_dir = format!("{}/", params.path_name);
+    let frames = folder_frames(&source_dir);
+    if frames.is_empty() {
+        return Err(format!("no frame_N.png in {source_dir}"));
+    }
+    let meta = match fs::read_to_string(format!("{source_dir}{META_FILE}")) {
+        Err(_) => None,
+        Ok(text) => match parse_meta(&text) {
+            Ok(meta) => Some((text, meta)),
+            Err(msg) => return Err(format!("{source_dir}{META_FILE}: {msg}")),
+        },
+    };
+    let frame_rate = match &meta {
+        Some((_, meta)) => Some(meta.frame_rate),
+        None => fs::read_to_string(format!("{source_dir}frame_rate")).ok()
+            .and_then(|it| it.trim().parse::<u32>().ok()),
+    }.filter(|&it| it > 0);
+    let Some(frame_rate) = frame_rate else {
+        return Err(format!("no meta.txt with the frame rate or frame_rate file in {source_dir}"));
+    };
+    if let Some(missing) = (0..frames.len()).find(|&i| meta.is_some() && frames[i].0 != i) {
+        return Err(format!("no frame_{missing}.png in {source_dir}"));
+    }
+    if !params.only_preview {
+        create_dir_all(params.dolphin_anim_path.as_str()).unwrap();
+    }
+    let duration = 1000.0 / frame_rate as f32;
+    let bar = new_progress(frames.len(), "Converting...");
+    let mut hashes = Vec::<u64>::new();
+    let mut unique = Vec::<Bitmap>::new();
+    let mut data = Vec::<FrameData>::new();
+    for (_, path) in &frames {
+        let image = image::open(path).map_err(|it| format!("{path}: {it}"))?.to_rgba8();
+        if image.width() > u8::MAX as u32 || image.height() > u8::MAX as u32 {
+            return Err(format!("{path} is too large for a bitmap"));
+        }
+        let bitmap = pixels2bm(&image, params);
+        let mut hasher = DefaultHasher::new();
+        bitmap.hash(&mut hasher);
+        let hash = hasher.finish();
+        // the meta already refers to the frames by their numbers
+        let index = match meta {
+            Some(_) => None,
+            None => hashes.iter().position(|&it| it == hash),
+        }.unwrap_or_else(|| {
+            hashes.push(hash);
+            unique.push(bitmap);
+            hashes.len() - 1
+        });
+        data.push(FrameData { index, duration });
+        bar.inc(1);
+    }
+    bar.finish();
+
+    let order = match &meta {
+        Some((_, meta)) => meta.frames_order.iter()
+            .map(|&it| it as usize)
+            .filter(|&it| it < unique.len())
+            .collect(),
+        None => data.iter().map(|it| it.index).collect::<Vec<usize>>(),
+    };
+    if !params.only_preview {
+        for (index, bitmap) in unique.iter().enumerate() {
+            let name = format!("{}_{index}", params.c_name);
+            fs::write(params.path_frame(index), encode(bitmap, params.format, params.layout, &name)).unwrap();
+        }
+        write_c_array(params, &unique, &order, frame_rate);
+    }
+    if !params.only_preview && params.format == OutputFormat::Bm {
+        let first = unique.first().unwrap();
+        let meta = meta.map(|(text, _)| text).unwrap_or_else(|| get_meta(first.width, first.height, &data, &params.recipe));
+        fs::write(params.meta_path.clone(), meta).unwrap();
+        if params.with_manifest {
+            write_manifest(params);
+        }
+    }
+    let data = order.iter().map(|&index| FrameData { index, duration }).collect::<Vec<FrameData>>();
+    if params.preview {
+        let preview_frames = unique.iter().map(|it| styled_preview(it, params)).collect();
+        bm2preview_gif(params, &params.preview_gif_path, &data, &preview_frames);
     }
+    if let Some(mode) = params.term_preview {
+        play(&unique, &data, mode, params.preview_scale, params.speed);
+    }
+    return Ok(());
+}
+
+/// The frame_N.png files sorted by their numbers
+fn folder_frames(dir: &str) -> Vec<(usize, String)> {
+    let mut frames = fs::read_dir(dir).unwrap()
+        .filter_map(|it| it.ok())
+        .filter_map(|it| {
+            let name = it.file_name().to_str()?.to_lowercase();
+            let number = name.strip_prefix("frame_")?.strip_suffix(".png")?.parse::<usize>().ok()?;
+            Some((number, it.path().to_str()?.to_string()))
+        })
+        .collect::<Vec<(usize, String)>>();
+    frames.sort_by_key(|it| it.0);
+    return frames;
+}
+
+// the same level for all frames keeps the static parts of an animation from flickering
+fn gif_threshold(params: &Params, frame_params: &FrameParams, frames: &[Frame]) -> Threshold {
+    if params.threshold.mode != ThresholdMode::Otsu || params.frame_threshold {
+        return params.threshold.clone();
+    }
+    let mut histogram = [0u32; 256];
+    for frame in frames {
+        add_to_histogram(&mut histogram, &to_gray(frame.buffer(), params, frame_params));
+    }
+    return params.threshold.around(otsu_level(&histogram));
+}
+
+fn write_c_array(params: &Params, frames: &[Bitmap], order: &[usize], frame_rate: u32) {
+    let Some(kind) = params.c_array else { return };
+    let c = c_source(&params.c_name, frames, order, frame_rate, kind, params.layout, params.c_icon);
+    fs::write(params.c_header_path.clone(), c.header).unwrap();
+    fs::write(params.c_source_path.clone(), c.source).unwrap();
 }
 
 fn write_manifest(params: &Params) {
     let manifest_path = Path::new(params.manifest_path.as_str());
     let with_header = params.replace_manifest || !manifest_path.exists();
-    let manifest_part = get_manifest(with_header, params.dolphin_anim_name.clone());
+    let manifest_part = get_manifest(with_header, params.dolphin_anim_name.clone(), &params.recipe.manifest);
     let mut manifest_file = OpenOptions::new()
-        .create(true).write(true).append(!params.replace_manifest)
+        .create(true).write(true).append(!params.replace_manifest).truncate(params.replace_manifest)
         .open(manifest_path)
         .unwrap();
     manifest_file.write(manifest_part.as_bytes()).unwrap();
 }
 
-fn bm2preview_gif(params: &Params, data: &Vec::<FrameData>, preview_frames: &Vec::<GrayImage>) {
+fn bm2preview_gif(params: &Params, path: &str, data: &Vec::<FrameData>, preview_frames: &Vec::<RgbImage>) {
     let bar = new_progress(preview_frames.len(), "Generating preview...");
     let mut frames = Vec::<Frame>::new();
     for fd in data {
@@ -163,40 +633,20 @@ fn bm2preview_gif(params: &Params, data: &Vec::<FrameData>, preview_frames: &Vec
         frames.push(frame);
         bar.inc(1);
     }
-    let preview_file = File::create(params.preview_gif_path.clone()).unwrap();
+    let preview_file = File::create(path).unwrap();
     let mut encoder = GifEncoder::new(preview_file);
     encoder.set_repeat(Repeat::Infinite).unwrap();
     encoder.encode_frames(frames.into_iter()).unwrap();
     bar.finish();
 }
 
-fn bm2preview(bitmap: &Bitmap, scale: u32) -> GrayImage {
-    let width = bitmap.width as u32;
-    let height = bitmap.height as u32;
-    let mut image = GrayImage::new(width * scale, height * scale);
-    for y in 0..height {
-        for x in 0..width {
-            // +1 because of the first byte is extra 0x00
-            let bit = bitmap.get(x, y);
-            if !bit {
-                for x in (x * scale)..(x * scale + scale) {
-                    for y in (y * scale)..(y * scale + scale) {
-                        image.put_pixel(x, y, Luma([255u8]));
-                    }
-                }
-            }
-        }
-    }
-    return image;
-}
-
-fn save_preview(img: &GrayImage, name: &str) {
+fn save_preview(img: &RgbImage, name: &str) {
     image::save_buffer_with_format(
         name,
         img,
         img.width(),
         img.height(),
-        ColorType::L8,
+        ColorType::Rgb8,
         ImageFormat::Png,
     ).unwrap();
 }
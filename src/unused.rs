@@ -1,24 +1,4 @@
-use image::{DynamicImage, GenericImage, Rgba};
-use crate::core::color::Color;
-
-const BYTE_LIMIT: u16 = 256;
-
-pub struct Color {
-    pub r: u8,
-    pub g: u8,
-    pub b: u8,
-}
-
-impl Color {
-    pub fn parse(value: u32) -> Color {
-        let limit = BYTE_LIMIT as u32;
-        Color {
-            r: (value / limit.pow(2) % limit) as u8,
-            g: (value / limit % limit.pow(2)) as u8,
-            b: (value % limit.pow(3)) as u8,
-        }
-    }
-}
+use image::GenericImage;
 
 fn try_get_pixel<T>(image: &T, x: i32, y: i32) -> Option<T::Pixel>
     where
@@ -59,37 +39,3 @@ fn for_each_mut<T, F>(image: &mut T, action: F)
         }
     }
 }
-
-fn abs_dif(first: u8, second: u8) -> u8 {
-    let mut dif = first as i16 - second as i16;
-    dif *= dif.signum();
-    dif as u8
-}
-
-fn remove_background(image: &mut DynamicImage, color: Color, to_visible: bool) {
-    let target: u8 = if to_visible { 0 } else { 255 };
-    for_each_mut(image, |image, pixel| {
-        let r = pixel[0];
-        let g = pixel[1];
-        let b = pixel[2];
-        match () {
-            _ if abs_dif(color.r, r) > 50 => None,
-            _ if abs_dif(color.g, g) > 50 => None,
-            _ if abs_dif(color.b, b) > 50 => None,
-            _ if abs_dif(abs_dif(color.r, color.g), abs_dif(r, g)) > 30 => None,
-            _ if abs_dif(abs_dif(color.g, color.b), abs_dif(g, b)) > 30 => None,
-            _ => Some(Rgba([target, target, target, pixel.0[3]]))
-        }
-    });
-}
-
-fn color_to_u32(color: &str) -> u32 {
-    if color.len() != 6 && color.len() != 8 {
-        panic!("Color must contains 6 or 8 0..f-chars ({})", color);
-    }
-    let mut color_int = u32::from_str_radix(color, 16).unwrap();
-    if color.len() == 6 {
-        color_int += 0xff000000;
-    }
-    return color_int;
-}